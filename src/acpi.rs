@@ -0,0 +1,1032 @@
+//! ACPI table discovery and parsing.
+//!
+//! The parsers work on byte slices and don't touch firmware. [`Acpi`] walks
+//! the tables in memory, starting from the RSDP in the configuration table.
+
+use crate::bytes::*;
+use crate::tables::config::*;
+use crate::tables::st::SystemTable;
+use crate::types::*;
+
+pub const RSDP_SIGNATURE: &[u8; 8] = b"RSD PTR ";
+
+pub const XSDT_SIGNATURE: &[u8; 4] = b"XSDT";
+pub const RSDT_SIGNATURE: &[u8; 4] = b"RSDT";
+pub const FADT_SIGNATURE: &[u8; 4] = b"FACP";
+pub const MADT_SIGNATURE: &[u8; 4] = b"APIC";
+pub const HPET_SIGNATURE: &[u8; 4] = b"HPET";
+pub const MCFG_SIGNATURE: &[u8; 4] = b"MCFG";
+pub const BGRT_SIGNATURE: &[u8; 4] = b"BGRT";
+pub const DSDT_SIGNATURE: &[u8; 4] = b"DSDT";
+pub const SSDT_SIGNATURE: &[u8; 4] = b"SSDT";
+
+// ACPI 1.0 RSDP, and the 2.0 extension
+const RSDP_V1_SIZE: usize = 20;
+const RSDP_V2_SIZE: usize = 36;
+
+pub const SDT_HEADER_SIZE: usize = 36;
+
+/// Root System Description Pointer.
+#[derive(Debug, Copy, Clone)]
+pub struct Rsdp<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Rsdp<'a> {
+    /// Checks the signature, the revision and the checksums that apply to
+    /// it. A 2.0 RSDP must give its length as exactly 36 bytes; no later
+    /// revision has extended it.
+    pub fn parse(bytes: &'a [u8]) -> Option<Self> {
+        if bytes.get(..8)? != RSDP_SIGNATURE || !checksum_ok(bytes.get(..RSDP_V1_SIZE)?) {
+            return None;
+        }
+        let size = match read_u8(bytes, 15)? {
+            0 => RSDP_V1_SIZE,
+            // revision 1 was never defined
+            1 => return None,
+            _ => {
+                let length = read_u32(bytes, 20)? as usize;
+                if length != RSDP_V2_SIZE || !checksum_ok(bytes.get(..length)?) {
+                    return None;
+                }
+                length
+            }
+        };
+        Some(Rsdp { bytes: &bytes[..size] })
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` must point at an RSDP that stays mapped for `'a`. Only the
+    /// bytes its revision defines are read, whatever its length field says.
+    pub unsafe fn from_ptr(ptr: *const VOID) -> Option<Self> {
+        let head = unsafe { core::slice::from_raw_parts(ptr as *const u8, RSDP_V1_SIZE) };
+        let size = match read_u8(head, 15)? {
+            0 | 1 => RSDP_V1_SIZE,
+            _ => RSDP_V2_SIZE,
+        };
+        Rsdp::parse(unsafe { core::slice::from_raw_parts(ptr as *const u8, size) })
+    }
+
+    pub fn revision(&self) -> u8 {
+        self.bytes[15]
+    }
+
+    pub fn oem_id(&self) -> [u8; 6] {
+        read_array(self.bytes, 9).unwrap()
+    }
+
+    pub fn rsdt_address(&self) -> u32 {
+        read_u32(self.bytes, 16).unwrap()
+    }
+
+    /// `None` for ACPI 1.0, or when the firmware left it zero.
+    pub fn xsdt_address(&self) -> Option<u64> {
+        read_u64(self.bytes, 24).filter(|&address| address != 0)
+    }
+}
+
+/// The header shared by all description tables.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SdtHeader {
+    pub signature: [u8; 4],
+    pub length: u32,
+    pub revision: u8,
+    pub checksum: u8,
+    pub oem_id: [u8; 6],
+    pub oem_table_id: [u8; 8],
+    pub oem_revision: u32,
+    pub creator_id: u32,
+    pub creator_revision: u32,
+}
+
+impl SdtHeader {
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        Some(SdtHeader {
+            signature: read_array(bytes, 0)?,
+            length: read_u32(bytes, 4)?,
+            revision: read_u8(bytes, 8)?,
+            checksum: read_u8(bytes, 9)?,
+            oem_id: read_array(bytes, 10)?,
+            oem_table_id: read_array(bytes, 16)?,
+            oem_revision: read_u32(bytes, 24)?,
+            creator_id: read_u32(bytes, 28)?,
+            creator_revision: read_u32(bytes, 32)?,
+        })
+    }
+}
+
+/// A description table with a valid length and checksum.
+#[derive(Debug, Copy, Clone)]
+pub struct AcpiTable<'a> {
+    header: SdtHeader,
+    bytes: &'a [u8],
+}
+
+impl<'a> AcpiTable<'a> {
+    /// Parses the table at the start of `bytes`; anything past its length
+    /// is ignored.
+    pub fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let header = SdtHeader::parse(bytes)?;
+        let length = header.length as usize;
+        if length < SDT_HEADER_SIZE || !checksum_ok(bytes.get(..length)?) {
+            return None;
+        }
+        Some(AcpiTable { header, bytes: &bytes[..length] })
+    }
+
+    /// # Safety
+    ///
+    /// `address` must hold a description table that stays mapped for `'a`.
+    pub unsafe fn from_address(address: u64) -> Option<Self> {
+        if address == 0 {
+            return None;
+        }
+        let length = unsafe { ((address + 4) as *const u32).read_unaligned() } as usize;
+        if length < SDT_HEADER_SIZE {
+            return None;
+        }
+        AcpiTable::parse(unsafe { core::slice::from_raw_parts(address as *const u8, length) })
+    }
+
+    pub fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+
+    pub fn signature(&self) -> &[u8; 4] {
+        &self.header.signature
+    }
+
+    /// The whole table, header included.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// The table contents after the header.
+    pub fn data(&self) -> &'a [u8] {
+        &self.bytes[SDT_HEADER_SIZE..]
+    }
+
+    /// Table addresses listed by an XSDT or RSDT; `None` for other tables.
+    pub fn sdt_entries(&self) -> Option<impl Iterator<Item = u64> + 'a> {
+        let entry_size = match self.signature() {
+            XSDT_SIGNATURE => 8,
+            RSDT_SIGNATURE => 4,
+            _ => return None,
+        };
+        Some(self.data().chunks_exact(entry_size).map(move |entry| {
+            if entry_size == 8 {
+                read_u64(entry, 0).unwrap()
+            } else {
+                read_u32(entry, 0).unwrap() as u64
+            }
+        }))
+    }
+
+    fn with_signature(bytes: &'a [u8], signature: &[u8; 4]) -> Option<Self> {
+        AcpiTable::parse(bytes).filter(|table| table.signature() == signature)
+    }
+}
+
+/// Generic Address Structure.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GenericAddress {
+    pub address_space_id: u8,
+    pub register_bit_width: u8,
+    pub register_bit_offset: u8,
+    pub access_size: u8,
+    pub address: u64,
+}
+
+impl GenericAddress {
+    pub const SYSTEM_MEMORY: u8 = 0;
+    pub const SYSTEM_IO: u8 = 1;
+
+    fn parse(bytes: &[u8], offset: usize) -> Option<Self> {
+        Some(GenericAddress {
+            address_space_id: read_u8(bytes, offset)?,
+            register_bit_width: read_u8(bytes, offset + 1)?,
+            register_bit_offset: read_u8(bytes, offset + 2)?,
+            access_size: read_u8(bytes, offset + 3)?,
+            address: read_u64(bytes, offset + 4)?,
+        })
+    }
+}
+
+/// Fixed ACPI Description Table. Fields that a table's revision doesn't
+/// cover read as `None`.
+#[derive(Debug, Copy, Clone)]
+pub struct Fadt<'a> {
+    table: AcpiTable<'a>,
+}
+
+impl<'a> Fadt<'a> {
+    // the ACPI 1.0 table ends after Flags
+    const MIN_LENGTH: usize = 116;
+
+    pub fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let table = AcpiTable::with_signature(bytes, FADT_SIGNATURE)?;
+        (table.bytes.len() >= Self::MIN_LENGTH).then_some(Fadt { table })
+    }
+
+    pub fn table(&self) -> &AcpiTable<'a> {
+        &self.table
+    }
+
+    fn bytes(&self) -> &'a [u8] {
+        self.table.bytes
+    }
+
+    /// FACS address, preferring the 64-bit field.
+    pub fn firmware_ctrl(&self) -> u64 {
+        read_u64(self.bytes(), 132)
+            .filter(|&address| address != 0)
+            .unwrap_or(read_u32(self.bytes(), 36).unwrap() as u64)
+    }
+
+    /// DSDT address, preferring the 64-bit field.
+    pub fn dsdt(&self) -> u64 {
+        read_u64(self.bytes(), 140)
+            .filter(|&address| address != 0)
+            .unwrap_or(read_u32(self.bytes(), 40).unwrap() as u64)
+    }
+
+    pub fn preferred_pm_profile(&self) -> u8 {
+        self.bytes()[45]
+    }
+
+    pub fn sci_interrupt(&self) -> u16 {
+        read_u16(self.bytes(), 46).unwrap()
+    }
+
+    pub fn smi_command_port(&self) -> u32 {
+        read_u32(self.bytes(), 48).unwrap()
+    }
+
+    pub fn pm_timer_block(&self) -> u32 {
+        read_u32(self.bytes(), 76).unwrap()
+    }
+
+    pub fn century(&self) -> u8 {
+        self.bytes()[108]
+    }
+
+    /// IA-PC boot architecture flags, e.g. legacy devices or an 8042.
+    pub fn iapc_boot_arch(&self) -> u16 {
+        read_u16(self.bytes(), 109).unwrap()
+    }
+
+    pub fn flags(&self) -> u32 {
+        read_u32(self.bytes(), 112).unwrap()
+    }
+
+    pub fn reset_register(&self) -> Option<GenericAddress> {
+        GenericAddress::parse(self.bytes(), 116)
+    }
+
+    pub fn reset_value(&self) -> Option<u8> {
+        read_u8(self.bytes(), 128)
+    }
+
+    /// ARM boot architecture flags, e.g. PSCI support.
+    pub fn arm_boot_arch(&self) -> Option<u16> {
+        read_u16(self.bytes(), 129)
+    }
+
+    pub fn minor_version(&self) -> Option<u8> {
+        read_u8(self.bytes(), 131)
+    }
+
+    pub fn hypervisor_vendor_id(&self) -> Option<u64> {
+        read_u64(self.bytes(), 268)
+    }
+}
+
+/// GIC CPU interface (GICC) entry of the MADT.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Gicc {
+    pub cpu_interface_number: u32,
+    pub acpi_processor_uid: u32,
+    pub flags: u32,
+    pub parking_protocol_version: u32,
+    pub performance_interrupt_gsiv: u32,
+    pub parked_address: u64,
+    pub physical_base_address: u64,
+    pub gicv: u64,
+    pub gich: u64,
+    pub vgic_maintenance_interrupt: u32,
+    pub gicr_base_address: u64,
+    pub mpidr: u64,
+    pub processor_power_efficiency_class: u8,
+}
+
+/// An interrupt controller structure in the MADT.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MadtEntry<'a> {
+    LocalApic {
+        acpi_processor_uid: u8,
+        apic_id: u8,
+        flags: u32,
+    },
+    IoApic {
+        io_apic_id: u8,
+        address: u32,
+        global_system_interrupt_base: u32,
+    },
+    InterruptSourceOverride {
+        bus: u8,
+        source: u8,
+        global_system_interrupt: u32,
+        flags: u16,
+    },
+    LocalApicNmi {
+        acpi_processor_uid: u8,
+        flags: u16,
+        lint: u8,
+    },
+    LocalApicAddressOverride {
+        address: u64,
+    },
+    LocalX2Apic {
+        x2apic_id: u32,
+        flags: u32,
+        acpi_processor_uid: u32,
+    },
+    Gicc(Gicc),
+    Gicd {
+        gic_id: u32,
+        physical_base_address: u64,
+        gic_version: u8,
+    },
+    Gicr {
+        discovery_range_base_address: u64,
+        discovery_range_length: u32,
+    },
+    GicIts {
+        its_id: u32,
+        physical_base_address: u64,
+    },
+    /// A type this crate doesn't decode, or one too short for its type.
+    Unknown {
+        entry_type: u8,
+        data: &'a [u8],
+    },
+}
+
+/// `flags` bit of local APIC and x2APIC entries for a usable processor.
+pub const MADT_LAPIC_ENABLED: u32 = 1 << 0;
+/// `flags` bit of local APIC and x2APIC entries for a processor that can be
+/// brought online later.
+pub const MADT_LAPIC_ONLINE_CAPABLE: u32 = 1 << 1;
+/// `flags` bit of GICC entries for a usable processor.
+pub const MADT_GICC_ENABLED: u32 = 1 << 0;
+
+impl<'a> MadtEntry<'a> {
+    // `data` starts at the type byte
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        let entry_type = data[0];
+        let entry = match entry_type {
+            0 => MadtEntry::LocalApic {
+                acpi_processor_uid: read_u8(data, 2)?,
+                apic_id: read_u8(data, 3)?,
+                flags: read_u32(data, 4)?,
+            },
+            1 => MadtEntry::IoApic {
+                io_apic_id: read_u8(data, 2)?,
+                address: read_u32(data, 4)?,
+                global_system_interrupt_base: read_u32(data, 8)?,
+            },
+            2 => MadtEntry::InterruptSourceOverride {
+                bus: read_u8(data, 2)?,
+                source: read_u8(data, 3)?,
+                global_system_interrupt: read_u32(data, 4)?,
+                flags: read_u16(data, 8)?,
+            },
+            4 => MadtEntry::LocalApicNmi {
+                acpi_processor_uid: read_u8(data, 2)?,
+                flags: read_u16(data, 3)?,
+                lint: read_u8(data, 5)?,
+            },
+            5 => MadtEntry::LocalApicAddressOverride {
+                address: read_u64(data, 4)?,
+            },
+            9 => MadtEntry::LocalX2Apic {
+                x2apic_id: read_u32(data, 4)?,
+                flags: read_u32(data, 8)?,
+                acpi_processor_uid: read_u32(data, 12)?,
+            },
+            11 => MadtEntry::Gicc(Gicc {
+                cpu_interface_number: read_u32(data, 4)?,
+                acpi_processor_uid: read_u32(data, 8)?,
+                flags: read_u32(data, 12)?,
+                parking_protocol_version: read_u32(data, 16)?,
+                performance_interrupt_gsiv: read_u32(data, 20)?,
+                parked_address: read_u64(data, 24)?,
+                physical_base_address: read_u64(data, 32)?,
+                gicv: read_u64(data, 40)?,
+                gich: read_u64(data, 48)?,
+                vgic_maintenance_interrupt: read_u32(data, 56)?,
+                gicr_base_address: read_u64(data, 60)?,
+                mpidr: read_u64(data, 68)?,
+                // added in ACPI 6.0, zero before that
+                processor_power_efficiency_class: read_u8(data, 76).unwrap_or(0),
+            }),
+            12 => MadtEntry::Gicd {
+                gic_id: read_u32(data, 4)?,
+                physical_base_address: read_u64(data, 8)?,
+                gic_version: read_u8(data, 20)?,
+            },
+            14 => MadtEntry::Gicr {
+                discovery_range_base_address: read_u64(data, 4)?,
+                discovery_range_length: read_u32(data, 12)?,
+            },
+            15 => MadtEntry::GicIts {
+                its_id: read_u32(data, 4)?,
+                physical_base_address: read_u64(data, 8)?,
+            },
+            _ => return None,
+        };
+        Some(entry)
+    }
+}
+
+/// Multiple APIC Description Table.
+#[derive(Debug, Copy, Clone)]
+pub struct Madt<'a> {
+    table: AcpiTable<'a>,
+}
+
+impl<'a> Madt<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let table = AcpiTable::with_signature(bytes, MADT_SIGNATURE)?;
+        (table.data().len() >= 8).then_some(Madt { table })
+    }
+
+    pub fn table(&self) -> &AcpiTable<'a> {
+        &self.table
+    }
+
+    /// 32-bit local APIC base; a `LocalApicAddressOverride` entry takes
+    /// precedence.
+    pub fn local_apic_address(&self) -> u32 {
+        read_u32(self.table.data(), 0).unwrap()
+    }
+
+    pub fn flags(&self) -> u32 {
+        read_u32(self.table.data(), 4).unwrap()
+    }
+
+    /// Whether the system also has dual 8259 PICs.
+    pub fn has_8259_pics(&self) -> bool {
+        self.flags() & 1 != 0
+    }
+
+    /// The interrupt controller structures, stopping at the first one whose
+    /// length is malformed.
+    pub fn entries(&self) -> impl Iterator<Item = MadtEntry<'a>> + 'a {
+        let mut rest = &self.table.data()[8..];
+        core::iter::from_fn(move || {
+            let length = *rest.get(1)? as usize;
+            if length < 2 || length > rest.len() {
+                return None;
+            }
+            let (data, next) = rest.split_at(length);
+            rest = next;
+            Some(MadtEntry::parse(data).unwrap_or(MadtEntry::Unknown {
+                entry_type: data[0],
+                data,
+            }))
+        })
+    }
+}
+
+/// High Precision Event Timer table.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Hpet {
+    pub event_timer_block_id: u32,
+    pub base_address: GenericAddress,
+    pub hpet_number: u8,
+    pub minimum_clock_tick: u16,
+    pub page_protection: u8,
+}
+
+impl Hpet {
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let table = AcpiTable::with_signature(bytes, HPET_SIGNATURE)?;
+        let data = table.data();
+        Some(Hpet {
+            event_timer_block_id: read_u32(data, 0)?,
+            base_address: GenericAddress::parse(data, 4)?,
+            hpet_number: read_u8(data, 16)?,
+            minimum_clock_tick: read_u16(data, 17)?,
+            page_protection: read_u8(data, 19)?,
+        })
+    }
+}
+
+/// One PCI Express ECAM range of the MCFG.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct McfgEntry {
+    pub base_address: u64,
+    pub pci_segment_group: u16,
+    pub start_bus: u8,
+    pub end_bus: u8,
+}
+
+/// PCI Express memory-mapped configuration table.
+#[derive(Debug, Copy, Clone)]
+pub struct Mcfg<'a> {
+    table: AcpiTable<'a>,
+}
+
+impl<'a> Mcfg<'a> {
+    // reserved bytes before the first entry
+    const ENTRIES_OFFSET: usize = 8;
+    const ENTRY_SIZE: usize = 16;
+
+    pub fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let table = AcpiTable::with_signature(bytes, MCFG_SIGNATURE)?;
+        (table.data().len() >= Self::ENTRIES_OFFSET).then_some(Mcfg { table })
+    }
+
+    pub fn table(&self) -> &AcpiTable<'a> {
+        &self.table
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = McfgEntry> + 'a {
+        self.table.data()[Self::ENTRIES_OFFSET..]
+            .chunks_exact(Self::ENTRY_SIZE)
+            .map(|entry| McfgEntry {
+                base_address: read_u64(entry, 0).unwrap(),
+                pci_segment_group: read_u16(entry, 8).unwrap(),
+                start_bus: entry[10],
+                end_bus: entry[11],
+            })
+    }
+}
+
+/// Boot Graphics Resource Table.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Bgrt {
+    pub version: u16,
+    pub status: u8,
+    /// 0 for a BMP image.
+    pub image_type: u8,
+    pub image_address: u64,
+    pub image_offset_x: u32,
+    pub image_offset_y: u32,
+}
+
+impl Bgrt {
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let table = AcpiTable::with_signature(bytes, BGRT_SIGNATURE)?;
+        let data = table.data();
+        Some(Bgrt {
+            version: read_u16(data, 0)?,
+            status: read_u8(data, 2)?,
+            image_type: read_u8(data, 3)?,
+            image_address: read_u64(data, 4)?,
+            image_offset_x: read_u32(data, 12)?,
+            image_offset_y: read_u32(data, 16)?,
+        })
+    }
+
+    /// Whether the image is currently shown on screen.
+    pub fn is_displayed(&self) -> bool {
+        self.status & 1 != 0
+    }
+}
+
+/// The ACPI tables in memory, reached through the XSDT, or the RSDT on
+/// ACPI 1.0 systems.
+#[derive(Debug, Copy, Clone)]
+pub struct Acpi {
+    rsdp: Rsdp<'static>,
+    root: AcpiTable<'static>,
+}
+
+impl Acpi {
+    /// # Safety
+    ///
+    /// `rsdp` must point at the platform's RSDP, with every table it
+    /// reaches mapped at its physical address for the rest of the program.
+    pub unsafe fn from_rsdp(rsdp: *const VOID) -> Option<Self> {
+        let rsdp = unsafe { Rsdp::from_ptr(rsdp)? };
+        let is_root = |table: &AcpiTable| table.sdt_entries().is_some();
+        // fall back to the RSDT when a broken XSDT doesn't validate
+        let root = rsdp.xsdt_address()
+            .and_then(|xsdt| unsafe { AcpiTable::from_address(xsdt) })
+            .filter(is_root)
+            .or_else(|| unsafe { AcpiTable::from_address(rsdp.rsdt_address() as u64) })
+            .filter(is_root)?;
+        Some(Acpi { rsdp, root })
+    }
+
+    pub fn rsdp(&self) -> &Rsdp<'static> {
+        &self.rsdp
+    }
+
+    /// The XSDT or RSDT.
+    pub fn root(&self) -> &AcpiTable<'static> {
+        &self.root
+    }
+
+    /// Every table listed by the root table; entries that fail validation
+    /// are skipped. The DSDT isn't listed, see [`Acpi::dsdt`].
+    pub fn tables(&self) -> impl Iterator<Item = AcpiTable<'static>> {
+        self.root
+            .sdt_entries()
+            .into_iter()
+            .flatten()
+            .filter_map(|address| unsafe { AcpiTable::from_address(address) })
+    }
+
+    /// The first table with `signature`.
+    pub fn find(&self, signature: &[u8; 4]) -> Option<AcpiTable<'static>> {
+        self.find_all(signature).next()
+    }
+
+    /// Every table with `signature`, e.g. all SSDTs, in root table order.
+    pub fn find_all<'s>(&'s self,
+                        signature: &'s [u8; 4]
+    ) -> impl Iterator<Item = AcpiTable<'static>> + 's {
+        self.tables().filter(move |table| table.signature() == signature)
+    }
+
+    pub fn fadt(&self) -> Option<Fadt<'static>> {
+        Fadt::parse(self.find(FADT_SIGNATURE)?.as_bytes())
+    }
+
+    /// The DSDT, found through the FADT.
+    pub fn dsdt(&self) -> Option<AcpiTable<'static>> {
+        let table = unsafe { AcpiTable::from_address(self.fadt()?.dsdt())? };
+        (table.signature() == DSDT_SIGNATURE).then_some(table)
+    }
+
+    pub fn ssdts(&self) -> impl Iterator<Item = AcpiTable<'static>> + '_ {
+        self.find_all(SSDT_SIGNATURE)
+    }
+
+    pub fn madt(&self) -> Option<Madt<'static>> {
+        Madt::parse(self.find(MADT_SIGNATURE)?.as_bytes())
+    }
+
+    pub fn hpet(&self) -> Option<Hpet> {
+        Hpet::parse(self.find(HPET_SIGNATURE)?.as_bytes())
+    }
+
+    pub fn mcfg(&self) -> Option<Mcfg<'static>> {
+        Mcfg::parse(self.find(MCFG_SIGNATURE)?.as_bytes())
+    }
+
+    pub fn bgrt(&self) -> Option<Bgrt> {
+        Bgrt::parse(self.find(BGRT_SIGNATURE)?.as_bytes())
+    }
+}
+
+impl SystemTable {
+    /// The ACPI tables, from the ACPI 2.0 entry or failing that the 1.0 one.
+    pub fn acpi(&self) -> Option<Acpi> {
+        [EFI_ACPI_TABLE_GUID, ACPI_TABLE_GUID]
+            .iter()
+            .filter_map(|guid| self.find_config_table(guid))
+            .find_map(|rsdp| unsafe { Acpi::from_rsdp(rsdp) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    // a table with a correct length and checksum around `data`
+    fn table(signature: &[u8; 4], revision: u8, data: &[u8]) -> Vec<u8> {
+        let mut bytes = alloc::vec![0u8; SDT_HEADER_SIZE];
+        bytes[0..4].copy_from_slice(signature);
+        write_u32(&mut bytes, 4, (SDT_HEADER_SIZE + data.len()) as u32);
+        bytes[8] = revision;
+        bytes[10..16].copy_from_slice(b"OEMID ");
+        bytes[16..24].copy_from_slice(b"OEMTABLE");
+        write_u32(&mut bytes, 24, 1);
+        bytes[28..32].copy_from_slice(b"TEST");
+        write_u32(&mut bytes, 32, 1);
+        bytes.extend_from_slice(data);
+        fix_checksum(&mut bytes, 9);
+        bytes
+    }
+
+    fn rsdp(revision: u8, rsdt: u32, xsdt: u64) -> Vec<u8> {
+        let size = if revision >= 2 { RSDP_V2_SIZE } else { RSDP_V1_SIZE };
+        let mut bytes = alloc::vec![0u8; size];
+        bytes[..8].copy_from_slice(RSDP_SIGNATURE);
+        bytes[9..15].copy_from_slice(b"OEMID ");
+        bytes[15] = revision;
+        write_u32(&mut bytes, 16, rsdt);
+        fix_checksum(&mut bytes[..RSDP_V1_SIZE], 8);
+        if revision >= 2 {
+            write_u32(&mut bytes, 20, RSDP_V2_SIZE as u32);
+            write_u64(&mut bytes, 24, xsdt);
+            fix_checksum(&mut bytes, 32);
+        }
+        bytes
+    }
+
+    // sets the extended length field, with the extended checksum still valid
+    fn with_length(mut rsdp: Vec<u8>, length: usize) -> Vec<u8> {
+        write_u32(&mut rsdp, 20, length as u32);
+        rsdp.resize(length.max(RSDP_V2_SIZE), 0);
+        fix_checksum(&mut rsdp, 32);
+        rsdp
+    }
+
+    // the local APIC address and flags that start the MADT, then `entries`
+    fn madt_data(flags: u32, entries: &[&[u8]]) -> Vec<u8> {
+        let mut data = alloc::vec![0u8; 8];
+        write_u32(&mut data, 0, 0xfee0_0000);
+        write_u32(&mut data, 4, flags);
+        for entry in entries {
+            data.extend_from_slice(entry);
+        }
+        data
+    }
+
+    fn madt_with(entry: &[u8]) -> Vec<u8> {
+        table(MADT_SIGNATURE, 5, &madt_data(0, &[entry]))
+    }
+
+    fn first_entry(madt: &[u8]) -> MadtEntry<'_> {
+        Madt::parse(madt).unwrap().entries().next().unwrap()
+    }
+
+    fn fadt_data() -> Vec<u8> {
+        let mut data = alloc::vec![0u8; 276 - SDT_HEADER_SIZE];
+        write_u32(&mut data, 40 - SDT_HEADER_SIZE, 0x1000);
+        write_u16(&mut data, 46 - SDT_HEADER_SIZE, 9);
+        data
+    }
+
+    #[test]
+    fn rsdp_v1() {
+        let v1 = rsdp(0, 0x7fe0_0000, 0);
+        let parsed = Rsdp::parse(&v1).unwrap();
+        assert_eq!(parsed.revision(), 0);
+        assert_eq!(parsed.rsdt_address(), 0x7fe0_0000);
+        assert_eq!(parsed.xsdt_address(), None);
+    }
+
+    #[test]
+    fn rsdp_v2() {
+        let v2 = rsdp(2, 0x7fe0_0000, 0x7fe1_0000);
+        let parsed = Rsdp::parse(&v2).unwrap();
+        assert_eq!(parsed.oem_id(), *b"OEMID ");
+        assert_eq!(parsed.xsdt_address(), Some(0x7fe1_0000));
+    }
+
+    #[test]
+    fn rsdp_rejects_revision_1() {
+        let mut v1 = rsdp(0, 0x1000, 0);
+        v1[15] = 1;
+        fix_checksum(&mut v1, 8);
+        assert!(Rsdp::parse(&v1).is_none());
+    }
+
+    #[test]
+    fn rsdp_rejects_bad_signature() {
+        let mut v2 = rsdp(2, 0x1000, 0x2000);
+        v2[0] = b'X';
+        assert!(Rsdp::parse(&v2).is_none());
+    }
+
+    #[test]
+    fn rsdp_rejects_bad_checksum() {
+        let mut v1 = rsdp(0, 0x1000, 0);
+        v1[16] ^= 1;
+        assert!(Rsdp::parse(&v1).is_none());
+    }
+
+    #[test]
+    fn rsdp_rejects_bad_extended_checksum() {
+        // the 1.0 checksum still holds, the extended one doesn't
+        let mut v2 = rsdp(2, 0x1000, 0x2000);
+        v2[24] ^= 1;
+        assert!(Rsdp::parse(&v2).is_none());
+    }
+
+    #[test]
+    fn rsdp_rejects_short_extended_length() {
+        let v2 = with_length(rsdp(2, 0x1000, 0x2000), RSDP_V2_SIZE - 1);
+        assert!(Rsdp::parse(&v2).is_none());
+    }
+
+    #[test]
+    fn rsdp_rejects_long_extended_length() {
+        let v2 = with_length(rsdp(2, 0x1000, 0x2000), RSDP_V2_SIZE + 4);
+        assert!(Rsdp::parse(&v2).is_none());
+    }
+
+    #[test]
+    fn rsdp_from_ptr_ignores_the_length_field() {
+        // a length field that runs far past the 36 bytes isn't followed
+        let mut v2 = rsdp(2, 0x1000, 0x2000);
+        write_u32(&mut v2, 20, 0x1000);
+        fix_checksum(&mut v2, 32);
+        assert!(unsafe { Rsdp::from_ptr(v2.as_ptr() as *const VOID) }.is_none());
+    }
+
+    #[test]
+    fn rsdp_from_ptr() {
+        let v2 = rsdp(2, 0x1000, 0x2000);
+        let parsed = unsafe { Rsdp::from_ptr(v2.as_ptr() as *const VOID) }.unwrap();
+        assert_eq!(parsed.xsdt_address(), Some(0x2000));
+    }
+
+    #[test]
+    fn table_ignores_bytes_past_length() {
+        let mut bytes = table(SSDT_SIGNATURE, 2, &[1, 2, 3]);
+        bytes.extend_from_slice(&[0xff; 4]);
+        let parsed = AcpiTable::parse(&bytes).unwrap();
+        assert_eq!(parsed.data(), &[1, 2, 3]);
+        assert_eq!(parsed.header().oem_table_id, *b"OEMTABLE");
+    }
+
+    #[test]
+    fn table_rejects_bad_checksum() {
+        let mut bytes = table(SSDT_SIGNATURE, 2, &[1, 2, 3]);
+        bytes[SDT_HEADER_SIZE] ^= 1;
+        assert!(AcpiTable::parse(&bytes).is_none());
+    }
+
+    #[test]
+    fn table_rejects_truncated_header() {
+        let bytes = table(SSDT_SIGNATURE, 2, &[1, 2, 3]);
+        assert!(AcpiTable::parse(&bytes[..20]).is_none());
+    }
+
+    #[test]
+    fn xsdt_entries() {
+        let mut data = alloc::vec![0u8; 16];
+        write_u64(&mut data, 0, 0x1000);
+        write_u64(&mut data, 8, 0x2000);
+        let xsdt = table(XSDT_SIGNATURE, 1, &data);
+        let entries: Vec<u64> = AcpiTable::parse(&xsdt).unwrap().sdt_entries().unwrap().collect();
+        assert_eq!(entries, [0x1000, 0x2000]);
+    }
+
+    #[test]
+    fn rsdt_entries() {
+        let mut data = alloc::vec![0u8; 8];
+        write_u32(&mut data, 0, 0x3000);
+        write_u32(&mut data, 4, 0x4000);
+        let rsdt = table(RSDT_SIGNATURE, 1, &data);
+        let entries: Vec<u64> = AcpiTable::parse(&rsdt).unwrap().sdt_entries().unwrap().collect();
+        assert_eq!(entries, [0x3000, 0x4000]);
+    }
+
+    #[test]
+    fn other_tables_have_no_sdt_entries() {
+        let ssdt = table(SSDT_SIGNATURE, 1, &[]);
+        assert!(AcpiTable::parse(&ssdt).unwrap().sdt_entries().is_none());
+    }
+
+    #[test]
+    fn madt_header() {
+        let bytes = table(MADT_SIGNATURE, 5, &madt_data(1, &[]));
+        let madt = Madt::parse(&bytes).unwrap();
+        assert_eq!(madt.local_apic_address(), 0xfee0_0000);
+        assert!(madt.has_8259_pics());
+        assert_eq!(madt.entries().count(), 0);
+    }
+
+    #[test]
+    fn madt_local_apic() {
+        assert_eq!(first_entry(&madt_with(&[0, 8, 1, 2, 1, 0, 0, 0])),
+                   MadtEntry::LocalApic { acpi_processor_uid: 1, apic_id: 2, flags: 1 });
+    }
+
+    #[test]
+    fn madt_io_apic() {
+        let mut entry = [1, 12, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        write_u32(&mut entry, 4, 0xfec0_0000);
+        assert_eq!(first_entry(&madt_with(&entry)), MadtEntry::IoApic {
+            io_apic_id: 4,
+            address: 0xfec0_0000,
+            global_system_interrupt_base: 0,
+        });
+    }
+
+    #[test]
+    fn madt_local_x2apic() {
+        let mut entry = [9, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        write_u32(&mut entry, 4, 300);
+        write_u32(&mut entry, 8, 1);
+        write_u32(&mut entry, 12, 7);
+        assert_eq!(first_entry(&madt_with(&entry)), MadtEntry::LocalX2Apic {
+            x2apic_id: 300,
+            flags: MADT_LAPIC_ENABLED,
+            acpi_processor_uid: 7,
+        });
+    }
+
+    #[test]
+    fn madt_gicc() {
+        // ACPI 6.3 length
+        let mut entry = [0u8; 82];
+        entry[0] = 11;
+        entry[1] = 82;
+        write_u32(&mut entry, 4, 3);
+        write_u32(&mut entry, 12, 1);
+        write_u64(&mut entry, 68, 0x8000_0103);
+        match first_entry(&madt_with(&entry)) {
+            MadtEntry::Gicc(gicc) => {
+                assert_eq!(gicc.cpu_interface_number, 3);
+                assert_eq!(gicc.flags, MADT_GICC_ENABLED);
+                assert_eq!(gicc.mpidr, 0x8000_0103);
+            }
+            other => panic!("unexpected entry {:?}", other),
+        }
+    }
+
+    #[test]
+    fn madt_unknown_entry() {
+        assert_eq!(first_entry(&madt_with(&[0x7f, 4, 0xaa, 0xbb])),
+                   MadtEntry::Unknown { entry_type: 0x7f, data: &[0x7f, 4, 0xaa, 0xbb] });
+    }
+
+    #[test]
+    fn madt_stops_at_malformed_entry() {
+        let data = madt_data(0, &[&[0, 8, 1, 2, 1, 0, 0, 0], &[0, 0xff, 0, 0]]);
+        let bytes = table(MADT_SIGNATURE, 5, &data);
+        assert_eq!(Madt::parse(&bytes).unwrap().entries().count(), 1);
+    }
+
+    #[test]
+    fn mcfg_entries() {
+        let mut data = alloc::vec![0u8; 24];
+        write_u64(&mut data, 8, 0xe000_0000);
+        data[19] = 0xff;
+        let bytes = table(MCFG_SIGNATURE, 1, &data);
+        let entries: Vec<McfgEntry> = Mcfg::parse(&bytes).unwrap().entries().collect();
+        assert_eq!(entries, [McfgEntry {
+            base_address: 0xe000_0000,
+            pci_segment_group: 0,
+            start_bus: 0,
+            end_bus: 0xff,
+        }]);
+    }
+
+    #[test]
+    fn typed_parse_checks_signature() {
+        let bytes = table(MCFG_SIGNATURE, 1, &[0; 8]);
+        assert!(Madt::parse(&bytes).is_none());
+    }
+
+    #[test]
+    fn hpet() {
+        let mut data = alloc::vec![0u8; 20];
+        write_u32(&mut data, 0, 0x8086_a201);
+        data[5] = 64;
+        write_u64(&mut data, 8, 0xfed0_0000);
+        data[17] = 0x80;
+        let hpet = Hpet::parse(&table(HPET_SIGNATURE, 1, &data)).unwrap();
+        assert_eq!(hpet.base_address.address, 0xfed0_0000);
+        assert_eq!(hpet.base_address.address_space_id, GenericAddress::SYSTEM_MEMORY);
+        assert_eq!(hpet.minimum_clock_tick, 0x80);
+    }
+
+    #[test]
+    fn bgrt() {
+        let mut data = alloc::vec![1, 0, 1, 0];
+        data.resize(20, 0);
+        write_u64(&mut data, 4, 0x7f00_0000);
+        write_u32(&mut data, 12, 100);
+        write_u32(&mut data, 16, 200);
+        let bgrt = Bgrt::parse(&table(BGRT_SIGNATURE, 1, &data)).unwrap();
+        assert!(bgrt.is_displayed());
+        assert_eq!(bgrt.image_address, 0x7f00_0000);
+        assert_eq!((bgrt.image_offset_x, bgrt.image_offset_y), (100, 200));
+    }
+
+    #[test]
+    fn fadt_32_bit_fields() {
+        let bytes = table(FADT_SIGNATURE, 6, &fadt_data());
+        let fadt = Fadt::parse(&bytes).unwrap();
+        assert_eq!(fadt.dsdt(), 0x1000);
+        assert_eq!(fadt.sci_interrupt(), 9);
+    }
+
+    #[test]
+    fn fadt_prefers_64_bit_addresses() {
+        let mut data = fadt_data();
+        write_u64(&mut data, 140 - SDT_HEADER_SIZE, 0x1_0000_0000);
+        let bytes = table(FADT_SIGNATURE, 6, &data);
+        assert_eq!(Fadt::parse(&bytes).unwrap().dsdt(), 0x1_0000_0000);
+    }
+
+    #[test]
+    fn fadt_acpi_1_layout() {
+        let bytes = table(FADT_SIGNATURE, 1, &fadt_data()[..116 - SDT_HEADER_SIZE]);
+        let fadt = Fadt::parse(&bytes).unwrap();
+        assert_eq!(fadt.dsdt(), 0x1000);
+        assert_eq!(fadt.reset_register(), None);
+    }
+}
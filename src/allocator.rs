@@ -8,6 +8,7 @@ use crate::*;
 #[allow(dead_code)]
 struct EfiAllocator;
 
+#[allow(dead_code)]
 trait LayoutExt {
     fn get_uefi_alignment(&self) -> UINTN;
 }
@@ -20,10 +21,13 @@ impl LayoutExt for Layout {
 
 unsafe impl GlobalAlloc for EfiAllocator {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let Some(bs) = BOOT_SERVICES.get() else {
+            return null_mut();
+        };
 
         let size = layout.size().max(layout.get_uefi_alignment()) as UINTN;
 
-        match BOOT_SERVICES.allocate_pool(
+        match bs.allocate_pool(
             EfiMemoryType::EfiLoaderData,
             size
         ) {
@@ -33,8 +37,12 @@ unsafe impl GlobalAlloc for EfiAllocator {
     }
 
     unsafe fn dealloc(&self, ptr: *mut u8, _layout: Layout) {
+        // after exit_boot_services the pool is gone; the memory simply leaks
+        let Some(bs) = BOOT_SERVICES.get() else {
+            return;
+        };
         if let Some(non_null) = core::ptr::NonNull::new(ptr as *mut VOID) {
-            let _ = BOOT_SERVICES.free_pool(non_null);
+            let _ = bs.free_pool(non_null);
         }
     }
 
@@ -48,5 +56,6 @@ unsafe impl GlobalAlloc for EfiAllocator {
 }
 
 //#[cfg(feature = "global-alloc")]
+#[cfg(not(test))]
 #[global_allocator]
 static ALLOCATOR: EfiAllocator = EfiAllocator;
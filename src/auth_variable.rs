@@ -0,0 +1,291 @@
+//! Time-based authenticated variable payloads (`EFI_VARIABLE_AUTHENTICATION_2`).
+//!
+//! Writing `PK`, `KEK`, `db` or `dbx` outside of setup mode takes a payload
+//! signed by the owner of the parent key. [`AuthVariableBuilder`] produces the
+//! bytes to sign and, given the detached PKCS#7 `SignedData`, the complete
+//! payload for `set_variable`. The signing itself is left to the caller.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::bytes::*;
+use crate::tables::rs::RuntimeServices;
+use crate::types::*;
+use crate::variable::*;
+
+pub const EFI_CERT_TYPE_PKCS7_GUID: EfiGuid = EfiGuid {
+    data1: 0x4aafd29d,
+    data2: 0x68df,
+    data3: 0x49ee,
+    data4: [0x8a,0xa9,0x34,0x7d,0x37,0x56,0x65,0xa7],
+};
+
+pub const WIN_CERT_REVISION: UINT16       = 0x0200;
+pub const WIN_CERT_TYPE_EFI_GUID: UINT16  = 0x0ef1;
+
+// dwLength, wRevision, wCertificateType, CertType
+const WIN_CERTIFICATE_UEFI_GUID_HEADER_SIZE: usize = 4 + 2 + 2 + 16;
+const AUTHENTICATION_2_HEADER_SIZE: usize = EfiTime::SIZE + WIN_CERTIFICATE_UEFI_GUID_HEADER_SIZE;
+
+/// Builds an `EFI_VARIABLE_AUTHENTICATION_2` payload for one variable write.
+#[derive(Debug, Clone)]
+pub struct AuthVariableBuilder {
+    name: String,
+    vendor_guid: EfiGuid,
+    attributes: VariableAttributes,
+    timestamp: EfiTime,
+    data: Vec<u8>,
+}
+
+impl AuthVariableBuilder {
+    /// `TIME_BASED_AUTHENTICATED_WRITE_ACCESS` is always added to
+    /// `attributes`. Only the date and time of `timestamp` are used; the
+    /// remaining fields must be zero in an authentication header.
+    pub fn new(name: &str,
+               vendor_guid: EfiGuid,
+               attributes: VariableAttributes,
+               timestamp: EfiTime,
+               data: Vec<u8>
+    ) -> Self {
+        AuthVariableBuilder {
+            name: String::from(name),
+            vendor_guid,
+            attributes: attributes | VariableAttributes::TIME_BASED_AUTHENTICATED_WRITE_ACCESS,
+            timestamp: EfiTime {
+                pad1: 0,
+                nanosecond: 0,
+                time_zone: 0,
+                daylight: 0,
+                pad2: 0,
+                ..timestamp
+            },
+            data,
+        }
+    }
+
+    /// Sets `APPEND_WRITE`, e.g. to add entries to `db` or `dbx`.
+    pub fn append(mut self) -> Self {
+        self.attributes |= VariableAttributes::APPEND_WRITE;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn vendor_guid(&self) -> &EfiGuid {
+        &self.vendor_guid
+    }
+
+    pub fn attributes(&self) -> VariableAttributes {
+        self.attributes
+    }
+
+    pub fn timestamp(&self) -> &EfiTime {
+        &self.timestamp
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The bytes covered by the signature:
+    /// name ‖ vendor GUID ‖ attributes ‖ timestamp ‖ data.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(
+            self.name.len() * 2 + 16 + 4 + EfiTime::SIZE + self.data.len());
+        // the signed name excludes the terminator
+        for c in self.name.encode_utf16() {
+            payload.extend_from_slice(&c.to_le_bytes());
+        }
+        payload.extend_from_slice(&self.vendor_guid.to_bytes());
+        payload.extend_from_slice(&self.attributes.bits().to_le_bytes());
+        payload.extend_from_slice(&self.timestamp.to_bytes());
+        payload.extend_from_slice(&self.data);
+        payload
+    }
+
+    /// Wraps the DER PKCS#7 `SignedData` over [`signing_payload`] and
+    /// returns the complete buffer to pass to `set_variable`.
+    ///
+    /// [`signing_payload`]: Self::signing_payload
+    pub fn build(&self, pkcs7: &[u8]) -> Vec<u8> {
+        let cert_length = (WIN_CERTIFICATE_UEFI_GUID_HEADER_SIZE + pkcs7.len()) as UINT32;
+        let mut payload = Vec::with_capacity(
+            AUTHENTICATION_2_HEADER_SIZE + pkcs7.len() + self.data.len());
+        payload.extend_from_slice(&self.timestamp.to_bytes());
+        payload.extend_from_slice(&cert_length.to_le_bytes());
+        payload.extend_from_slice(&WIN_CERT_REVISION.to_le_bytes());
+        payload.extend_from_slice(&WIN_CERT_TYPE_EFI_GUID.to_le_bytes());
+        payload.extend_from_slice(&EFI_CERT_TYPE_PKCS7_GUID.to_bytes());
+        payload.extend_from_slice(pkcs7);
+        payload.extend_from_slice(&self.data);
+        payload
+    }
+}
+
+/// A parsed `EFI_VARIABLE_AUTHENTICATION_2` header and the data after it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VariableAuthentication2<'a> {
+    pub timestamp: EfiTime,
+    /// `CertType` of the certificate, `EFI_CERT_TYPE_PKCS7_GUID` for
+    /// payloads accepted by the firmware.
+    pub cert_type: EfiGuid,
+    pub cert_data: &'a [u8],
+    /// The new variable contents.
+    pub data: &'a [u8],
+}
+
+impl<'a> VariableAuthentication2<'a> {
+    /// Parses an authenticated payload, as produced by
+    /// [`AuthVariableBuilder::build`].
+    pub fn parse(bytes: &'a [u8]) -> Option<Self> {
+        if bytes.len() < AUTHENTICATION_2_HEADER_SIZE {
+            return None;
+        }
+        let timestamp = EfiTime::from_bytes(read_array(bytes, 0)?);
+        let cert = &bytes[EfiTime::SIZE..];
+        let length = read_u32(cert, 0)? as usize;
+        let revision = read_u16(cert, 4)?;
+        let certificate_type = read_u16(cert, 6)?;
+        if revision != WIN_CERT_REVISION
+            || certificate_type != WIN_CERT_TYPE_EFI_GUID
+            || length < WIN_CERTIFICATE_UEFI_GUID_HEADER_SIZE
+            || length > cert.len()
+        {
+            return None;
+        }
+        Some(VariableAuthentication2 {
+            timestamp,
+            cert_type: EfiGuid::from_bytes(read_array(cert, 8)?),
+            cert_data: &cert[WIN_CERTIFICATE_UEFI_GUID_HEADER_SIZE..length],
+            data: &cert[length..],
+        })
+    }
+}
+
+impl RuntimeServices {
+    /// Writes the variable described by `builder`, signed with `pkcs7`.
+    pub fn set_authenticated_variable(&self,
+                                      builder: &AuthVariableBuilder,
+                                      pkcs7: &[u8]
+    ) -> Result<(), EfiStatus> {
+        self.set_variable(&builder.name,
+                          &builder.vendor_guid,
+                          builder.attributes,
+                          &builder.build(pkcs7))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secure_boot::EFI_IMAGE_SECURITY_DATABASE_GUID;
+
+    fn timestamp() -> EfiTime {
+        EfiTime {
+            year: 2024,
+            month: 3,
+            day: 14,
+            hour: 15,
+            minute: 9,
+            second: 26,
+            pad1: 0,
+            nanosecond: 535_897_932,
+            time_zone: -60,
+            daylight: EFI_TIME_IN_DAYLIGHT,
+            pad2: 0,
+        }
+    }
+
+    fn builder() -> AuthVariableBuilder {
+        AuthVariableBuilder::new("db",
+                                 EFI_IMAGE_SECURITY_DATABASE_GUID,
+                                 VariableAttributes::NV_BS_RT,
+                                 timestamp(),
+                                 alloc::vec![0xaa, 0xbb, 0xcc])
+    }
+
+    #[test]
+    fn timestamp_keeps_only_date_and_time() {
+        let ts = builder().timestamp().to_bytes();
+        assert_eq!(ts, [0xe8, 0x07, 3, 14, 15, 9, 26, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn attributes_are_time_based() {
+        let b = builder();
+        assert_eq!(b.attributes().bits(), 0x27);
+        assert_eq!(b.append().attributes().bits(), 0x67);
+    }
+
+    #[test]
+    fn signing_payload_layout() {
+        let payload = builder().signing_payload();
+        let mut expected = alloc::vec![b'd', 0, b'b', 0];
+        expected.extend_from_slice(&[0xcb, 0xb2, 0x19, 0xd7, 0x3a, 0x3d, 0x96, 0x45,
+                                     0xa3, 0xbc, 0xda, 0xd0, 0x0e, 0x67, 0x65, 0x6f]);
+        expected.extend_from_slice(&[0x27, 0, 0, 0]);
+        expected.extend_from_slice(&builder().timestamp().to_bytes());
+        expected.extend_from_slice(&[0xaa, 0xbb, 0xcc]);
+        assert_eq!(payload, expected);
+    }
+
+    #[test]
+    fn build_header_layout() {
+        let payload = builder().build(&[1, 2, 3, 4, 5]);
+        assert_eq!(payload.len(), 16 + 24 + 5 + 3);
+        // dwLength covers the WIN_CERTIFICATE_UEFI_GUID header and PKCS#7
+        assert_eq!(&payload[16..20], &29u32.to_le_bytes());
+        assert_eq!(&payload[20..24], &[0x00, 0x02, 0xf1, 0x0e]);
+        assert_eq!(&payload[24..40], &EFI_CERT_TYPE_PKCS7_GUID.to_bytes());
+        assert_eq!(&payload[40..45], &[1, 2, 3, 4, 5]);
+        assert_eq!(&payload[45..], &[0xaa, 0xbb, 0xcc]);
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        let b = builder();
+        let payload = b.build(&[9; 40]);
+        let parsed = VariableAuthentication2::parse(&payload).unwrap();
+        assert_eq!(parsed.timestamp, *b.timestamp());
+        assert_eq!(parsed.cert_type, EFI_CERT_TYPE_PKCS7_GUID);
+        assert_eq!(parsed.cert_data, &[9; 40]);
+        assert_eq!(parsed.data, b.data());
+    }
+
+    #[test]
+    fn parse_empty_data() {
+        let b = AuthVariableBuilder::new("dbx",
+                                         EFI_IMAGE_SECURITY_DATABASE_GUID,
+                                         VariableAttributes::NV_BS_RT,
+                                         timestamp(),
+                                         Vec::new());
+        let payload = b.build(&[7; 8]);
+        let parsed = VariableAuthentication2::parse(&payload).unwrap();
+        assert_eq!(parsed.cert_data, &[7; 8]);
+        assert!(parsed.data.is_empty());
+    }
+
+    #[test]
+    fn parse_rejects_malformed() {
+        let payload = builder().build(&[1, 2, 3]);
+        assert!(VariableAuthentication2::parse(&payload[..39]).is_none());
+
+        let mut bad_type = payload.clone();
+        bad_type[22] = 0x02;
+        assert!(VariableAuthentication2::parse(&bad_type).is_none());
+
+        let mut bad_revision = payload.clone();
+        bad_revision[21] = 0x01;
+        assert!(VariableAuthentication2::parse(&bad_revision).is_none());
+
+        let mut too_long = payload.clone();
+        too_long[16..20].copy_from_slice(&1000u32.to_le_bytes());
+        assert!(VariableAuthentication2::parse(&too_long).is_none());
+
+        let mut too_short = payload;
+        too_short[16..20].copy_from_slice(&8u32.to_le_bytes());
+        assert!(VariableAuthentication2::parse(&too_short).is_none());
+    }
+}
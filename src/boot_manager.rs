@@ -0,0 +1,352 @@
+//! Typed access to the boot manager variables in the `EFI_GLOBAL_VARIABLE`
+//! namespace.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::bytes::*;
+use crate::proto::device_path::DevicePathBuf;
+use crate::tables::rs::RuntimeServices;
+use crate::types::*;
+use crate::variable::*;
+
+pub const LOAD_OPTION_ACTIVE: UINT32            = 0x00000001;
+pub const LOAD_OPTION_FORCE_RECONNECT: UINT32   = 0x00000002;
+pub const LOAD_OPTION_HIDDEN: UINT32            = 0x00000008;
+pub const LOAD_OPTION_CATEGORY: UINT32          = 0x00001f00;
+pub const LOAD_OPTION_CATEGORY_BOOT: UINT32     = 0x00000000;
+pub const LOAD_OPTION_CATEGORY_APP: UINT32      = 0x00000100;
+
+pub const EFI_OS_INDICATIONS_BOOT_TO_FW_UI: UINT64                      = 0x0000000000000001;
+pub const EFI_OS_INDICATIONS_TIMESTAMP_REVOCATION: UINT64               = 0x0000000000000002;
+pub const EFI_OS_INDICATIONS_FILE_CAPSULE_DELIVERY_SUPPORTED: UINT64    = 0x0000000000000004;
+pub const EFI_OS_INDICATIONS_FMP_CAPSULE_SUPPORTED: UINT64              = 0x0000000000000008;
+pub const EFI_OS_INDICATIONS_CAPSULE_RESULT_VAR_SUPPORTED: UINT64       = 0x0000000000000010;
+pub const EFI_OS_INDICATIONS_START_OS_RECOVERY: UINT64                  = 0x0000000000000020;
+pub const EFI_OS_INDICATIONS_START_PLATFORM_RECOVERY: UINT64            = 0x0000000000000040;
+pub const EFI_OS_INDICATIONS_JSON_CONFIG_DATA_REFRESH: UINT64           = 0x0000000000000080;
+
+/// Which family of load option variables to address.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LoadOptionKind {
+    /// `Boot####`, ordered by `BootOrder`.
+    Boot,
+    /// `Driver####`, ordered by `DriverOrder`.
+    Driver,
+    /// `SysPrep####`, ordered by `SysPrepOrder`.
+    SysPrep,
+}
+
+impl LoadOptionKind {
+    fn prefix(&self) -> &'static str {
+        match self {
+            LoadOptionKind::Boot => "Boot",
+            LoadOptionKind::Driver => "Driver",
+            LoadOptionKind::SysPrep => "SysPrep",
+        }
+    }
+
+    /// Name of the option variable, e.g. `Boot0003`.
+    pub fn variable_name(&self, number: u16) -> String {
+        format!("{}{:04X}", self.prefix(), number)
+    }
+
+    /// Name of the matching order variable, e.g. `BootOrder`.
+    pub fn order_variable_name(&self) -> String {
+        format!("{}Order", self.prefix())
+    }
+}
+
+/// An `EFI_LOAD_OPTION`, the payload of `Boot####` and `Driver####`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadOption {
+    pub attributes: UINT32,
+    pub description: String,
+    /// The first path locates the option; any others are optional and
+    /// their meaning is defined by the loaded image.
+    pub file_paths: Vec<DevicePathBuf>,
+    pub optional_data: Vec<u8>,
+}
+
+impl LoadOption {
+    pub fn is_active(&self) -> bool {
+        self.attributes & LOAD_OPTION_ACTIVE != 0
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.attributes & LOAD_OPTION_HIDDEN != 0
+    }
+
+    pub fn category(&self) -> UINT32 {
+        self.attributes & LOAD_OPTION_CATEGORY
+    }
+
+    /// Parses a serialized `EFI_LOAD_OPTION`.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let attributes = read_u32(bytes, 0)?;
+        let path_list_len = read_u16(bytes, 4)? as usize;
+
+        let mut offset = 6;
+        let mut description = Vec::new();
+        loop {
+            let c = read_u16(bytes, offset)?;
+            offset += 2;
+            if c == 0 {
+                break;
+            }
+            description.push(c);
+        }
+
+        let mut path_list = bytes.get(offset..offset + path_list_len)?;
+        let mut file_paths = Vec::new();
+        while !path_list.is_empty() {
+            let path = DevicePathBuf::from_bytes(path_list)?;
+            path_list = &path_list[path.as_bytes().len()..];
+            file_paths.push(path);
+        }
+        offset += path_list_len;
+
+        Some(LoadOption {
+            attributes,
+            description: String::from_utf16(&description).ok()?,
+            file_paths,
+            optional_data: bytes[offset..].to_vec(),
+        })
+    }
+
+    /// Serializes into the `EFI_LOAD_OPTION` layout. Fails with
+    /// `EfiStatus::INVALID_PARAMETER` if the file paths take more than the
+    /// 64 KiB `FilePathListLength` can describe.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EfiStatus> {
+        let path_list_len: usize = self.file_paths.iter().map(|p| p.as_bytes().len()).sum();
+        let path_list_len = u16::try_from(path_list_len)
+            .map_err(|_| EfiStatus::INVALID_PARAMETER)?;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.attributes.to_le_bytes());
+        bytes.extend_from_slice(&path_list_len.to_le_bytes());
+        for c in self.description.encode_utf16().chain(core::iter::once(0)) {
+            bytes.extend_from_slice(&c.to_le_bytes());
+        }
+        for path in &self.file_paths {
+            bytes.extend_from_slice(path.as_bytes());
+        }
+        bytes.extend_from_slice(&self.optional_data);
+        Ok(bytes)
+    }
+}
+
+fn to_u16s(data: &[u8]) -> Vec<u16> {
+    data.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect()
+}
+
+fn from_u16s(values: &[u16]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn not_found_as_none<T>(result: Result<T, EfiStatus>) -> Result<Option<T>, EfiStatus> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(EfiStatus::NOT_FOUND) => Ok(None),
+        Err(status) => Err(status),
+    }
+}
+
+impl RuntimeServices {
+    fn get_global(&self, name: &str) -> Result<Vec<u8>, EfiStatus> {
+        self.get_variable(name, &EFI_GLOBAL_VARIABLE).map(|(data, _)| data)
+    }
+
+    fn get_global_u16(&self, name: &str) -> Result<u16, EfiStatus> {
+        let data = self.get_global(name)?;
+        read_u16(&data, 0).ok_or(EfiStatus::BAD_BUFFER_SIZE)
+    }
+
+    fn set_global(&self, name: &str, data: &[u8]) -> Result<(), EfiStatus> {
+        self.set_variable(name, &EFI_GLOBAL_VARIABLE, VariableAttributes::NV_BS_RT, data)
+    }
+
+    /// `BootOrder`, or an empty list if it doesn't exist.
+    pub fn boot_order(&self) -> Result<Vec<u16>, EfiStatus> {
+        self.load_option_order(LoadOptionKind::Boot)
+    }
+
+    pub fn set_boot_order(&self, order: &[u16]) -> Result<(), EfiStatus> {
+        self.set_load_option_order(LoadOptionKind::Boot, order)
+    }
+
+    /// `BootOrder`, `DriverOrder` or `SysPrepOrder`; empty if missing.
+    pub fn load_option_order(&self, kind: LoadOptionKind) -> Result<Vec<u16>, EfiStatus> {
+        let data = not_found_as_none(self.get_global(&kind.order_variable_name()))?;
+        Ok(data.map(|d| to_u16s(&d)).unwrap_or_default())
+    }
+
+    pub fn set_load_option_order(&self,
+                                 kind: LoadOptionKind,
+                                 order: &[u16]
+    ) -> Result<(), EfiStatus> {
+        self.set_global(&kind.order_variable_name(), &from_u16s(order))
+    }
+
+    /// The option to try on the next boot only, if set.
+    pub fn boot_next(&self) -> Result<Option<u16>, EfiStatus> {
+        not_found_as_none(self.get_global_u16("BootNext"))
+    }
+
+    pub fn set_boot_next(&self, number: u16) -> Result<(), EfiStatus> {
+        self.set_global("BootNext", &number.to_le_bytes())
+    }
+
+    pub fn clear_boot_next(&self) -> Result<(), EfiStatus> {
+        match self.delete_variable("BootNext", &EFI_GLOBAL_VARIABLE) {
+            Err(EfiStatus::NOT_FOUND) => Ok(()),
+            result => result,
+        }
+    }
+
+    /// The option selected for the current boot.
+    pub fn boot_current(&self) -> Result<u16, EfiStatus> {
+        self.get_global_u16("BootCurrent")
+    }
+
+    /// Seconds the boot manager waits before booting; `0xffff` waits
+    /// indefinitely.
+    pub fn timeout(&self) -> Result<u16, EfiStatus> {
+        self.get_global_u16("Timeout")
+    }
+
+    pub fn set_timeout(&self, seconds: u16) -> Result<(), EfiStatus> {
+        self.set_global("Timeout", &seconds.to_le_bytes())
+    }
+
+    /// `OsIndications`, or zero if it doesn't exist.
+    pub fn os_indications(&self) -> Result<UINT64, EfiStatus> {
+        self.get_global_u64("OsIndications").map(|v| v.unwrap_or(0))
+    }
+
+    pub fn set_os_indications(&self, indications: UINT64) -> Result<(), EfiStatus> {
+        self.set_global("OsIndications", &indications.to_le_bytes())
+    }
+
+    /// `OsIndicationsSupported`, or zero if it doesn't exist.
+    pub fn os_indications_supported(&self) -> Result<UINT64, EfiStatus> {
+        self.get_global_u64("OsIndicationsSupported").map(|v| v.unwrap_or(0))
+    }
+
+    fn get_global_u64(&self, name: &str) -> Result<Option<UINT64>, EfiStatus> {
+        let Some(data) = not_found_as_none(self.get_global(name))? else {
+            return Ok(None);
+        };
+        read_u64(&data, 0).map(Some).ok_or(EfiStatus::BAD_BUFFER_SIZE)
+    }
+
+    /// Reads `Boot####`, `Driver####` or `SysPrep####`. A malformed option
+    /// is reported as `EfiStatus::VOLUME_CORRUPTED`.
+    pub fn load_option(&self,
+                       kind: LoadOptionKind,
+                       number: u16
+    ) -> Result<LoadOption, EfiStatus> {
+        let data = self.get_global(&kind.variable_name(number))?;
+        LoadOption::parse(&data).ok_or(EfiStatus::VOLUME_CORRUPTED)
+    }
+
+    pub fn set_load_option(&self,
+                           kind: LoadOptionKind,
+                           number: u16,
+                           option: &LoadOption
+    ) -> Result<(), EfiStatus> {
+        self.set_global(&kind.variable_name(number), &option.to_bytes()?)
+    }
+
+    pub fn delete_load_option(&self,
+                              kind: LoadOptionKind,
+                              number: u16
+    ) -> Result<(), EfiStatus> {
+        self.delete_variable(&kind.variable_name(number), &EFI_GLOBAL_VARIABLE)
+    }
+
+    /// Lowest option number without a variable, for creating new entries.
+    pub fn free_load_option_number(&self, kind: LoadOptionKind) -> Result<u16, EfiStatus> {
+        for number in 0..=u16::MAX {
+            match self.get_variable(&kind.variable_name(number), &EFI_GLOBAL_VARIABLE) {
+                Err(EfiStatus::NOT_FOUND) => return Ok(number),
+                Err(status) => return Err(status),
+                Ok(_) => {}
+            }
+        }
+        Err(EfiStatus::OUT_OF_RESOURCES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option() -> LoadOption {
+        LoadOption {
+            attributes: LOAD_OPTION_ACTIVE,
+            description: String::from("Linux"),
+            file_paths: alloc::vec![
+                DevicePathBuf::file_path("\\EFI\\linux\\vmlinuz.efi").unwrap(),
+                DevicePathBuf::file_path("\\EFI\\linux\\initrd").unwrap(),
+            ],
+            optional_data: alloc::vec![b'q', 0, b'u', 0],
+        }
+    }
+
+    #[test]
+    fn to_bytes_attributes() {
+        let bytes = option().to_bytes().unwrap();
+        assert_eq!(read_u32(&bytes, 0), Some(LOAD_OPTION_ACTIVE));
+    }
+
+    #[test]
+    fn to_bytes_path_list_length() {
+        let bytes = option().to_bytes().unwrap();
+        let path_list_len = option().file_paths.iter().map(|p| p.as_bytes().len()).sum::<usize>();
+        assert_eq!(read_u16(&bytes, 4), Some(path_list_len as u16));
+    }
+
+    #[test]
+    fn to_bytes_description_is_nul_terminated_ucs2() {
+        let bytes = option().to_bytes().unwrap();
+        assert_eq!(&bytes[6..18], &[b'L', 0, b'i', 0, b'n', 0, b'u', 0, b'x', 0, 0, 0]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let bytes = option().to_bytes().unwrap();
+        assert_eq!(LoadOption::parse(&bytes), Some(option()));
+    }
+
+    #[test]
+    fn parse_rejects_unterminated_description() {
+        let bytes = option().to_bytes().unwrap();
+        // cut inside "Linux", before its terminator
+        assert!(LoadOption::parse(&bytes[..11]).is_none());
+    }
+
+    #[test]
+    fn parse_rejects_truncated_attributes() {
+        let bytes = option().to_bytes().unwrap();
+        assert!(LoadOption::parse(&bytes[..3]).is_none());
+    }
+
+    #[test]
+    fn parse_rejects_path_list_past_end() {
+        let mut bytes = option().to_bytes().unwrap();
+        let len = bytes.len() as u16;
+        write_u16(&mut bytes, 4, len);
+        assert!(LoadOption::parse(&bytes).is_none());
+    }
+
+    #[test]
+    fn to_bytes_rejects_oversized_path_list() {
+        let name = "x".repeat(30_000);
+        let path = DevicePathBuf::file_path(&name).unwrap();
+        let mut option = option();
+        option.file_paths = alloc::vec![path.clone(), path];
+        assert_eq!(option.to_bytes(), Err(EfiStatus::INVALID_PARAMETER));
+    }
+}
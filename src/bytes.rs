@@ -0,0 +1,51 @@
+//! Bounds-checked little-endian reads and checksums for parsing firmware
+//! tables.
+
+pub(crate) fn read_u8(bytes: &[u8], offset: usize) -> Option<u8> {
+    bytes.get(offset).copied()
+}
+
+pub(crate) fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(read_array(bytes, offset)?))
+}
+
+pub(crate) fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(read_array(bytes, offset)?))
+}
+
+pub(crate) fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(read_array(bytes, offset)?))
+}
+
+pub(crate) fn read_array<const N: usize>(bytes: &[u8], offset: usize) -> Option<[u8; N]> {
+    bytes.get(offset..offset.checked_add(N)?)?.try_into().ok()
+}
+
+/// Whether the bytes sum to zero, as ACPI and SMBIOS checksums require.
+pub(crate) fn checksum_ok(bytes: &[u8]) -> bool {
+    bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) == 0
+}
+
+/// Sets `bytes[offset]` so that `bytes` passes [`checksum_ok`].
+#[cfg(test)]
+pub(crate) fn fix_checksum(bytes: &mut [u8], offset: usize) {
+    bytes[offset] = 0;
+    let sum = bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+    bytes[offset] = sum.wrapping_neg();
+}
+
+/// Writes `value` little-endian at `offset`, the inverse of [`read_u16`].
+#[cfg(test)]
+pub(crate) fn write_u16(bytes: &mut [u8], offset: usize, value: u16) {
+    bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+pub(crate) fn write_u32(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+pub(crate) fn write_u64(bytes: &mut [u8], offset: usize, value: u64) {
+    bytes[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
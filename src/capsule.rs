@@ -0,0 +1,279 @@
+//! Capsule updates through the runtime services.
+
+use alloc::vec::Vec;
+
+use crate::memory::{PageAllocType, PageAllocation};
+use crate::tables::rs::RuntimeServices;
+use crate::types::*;
+use crate::*;
+
+const HEADER_SIZE: usize = core::mem::size_of::<EfiCapsuleHeader>();
+
+/// A capsule image, header and payload, in page-aligned physical memory.
+pub struct Capsule {
+    pages: PageAllocation,
+    size: usize,
+}
+
+impl Capsule {
+    /// Prefixes `payload` with a capsule header for `guid`.
+    ///
+    /// Fails with `EfiStatus::INVALID_PARAMETER` if `flags` sets
+    /// `CAPSULE_FLAGS_POPULATE_SYSTEM_TABLE` or `CAPSULE_FLAGS_INITIATE_RESET`
+    /// without `CAPSULE_FLAGS_PERSIST_ACROSS_RESET`.
+    pub fn new(guid: EfiGuid, flags: UINT32, payload: &[u8]) -> Result<Self, EfiStatus> {
+        let size = HEADER_SIZE + payload.len();
+        let header = EfiCapsuleHeader {
+            capsule_guid: guid,
+            header_size: HEADER_SIZE as UINT32,
+            flags,
+            capsule_image_size: u32::try_from(size).map_err(|_| EfiStatus::BAD_BUFFER_SIZE)?,
+        };
+        let mut capsule = Capsule::allocate(&header)?;
+        capsule.pages.as_mut_slice()[HEADER_SIZE..size].copy_from_slice(payload);
+        Ok(capsule)
+    }
+
+    /// Copies a complete capsule image that starts with its own header,
+    /// e.g. one read from a file.
+    pub fn from_bytes(image: &[u8]) -> Result<Self, EfiStatus> {
+        if image.len() < HEADER_SIZE {
+            return Err(EfiStatus::BAD_BUFFER_SIZE);
+        }
+        let header = unsafe { (image.as_ptr() as *const EfiCapsuleHeader).read_unaligned() };
+        if header.capsule_image_size as usize != image.len()
+            || (header.header_size as usize) < HEADER_SIZE
+            || header.header_size > header.capsule_image_size
+        {
+            return Err(EfiStatus::BAD_BUFFER_SIZE);
+        }
+        let mut capsule = Capsule::allocate(&header)?;
+        capsule.pages.as_mut_slice()[..image.len()].copy_from_slice(image);
+        Ok(capsule)
+    }
+
+    fn allocate(header: &EfiCapsuleHeader) -> Result<Self, EfiStatus> {
+        let persist = header.flags & CAPSULE_FLAGS_PERSIST_ACROSS_RESET != 0;
+        let needs_persist = CAPSULE_FLAGS_POPULATE_SYSTEM_TABLE | CAPSULE_FLAGS_INITIATE_RESET;
+        if !persist && header.flags & needs_persist != 0 {
+            return Err(EfiStatus::INVALID_PARAMETER);
+        }
+        // a persisting capsule is read at the next reset, which may come
+        // long after the OS has reclaimed boot services memory
+        let memory_type = if persist {
+            EfiMemoryType::EfiRuntimeServicesData
+        } else {
+            EfiMemoryType::EfiBootServicesData
+        };
+        let size = header.capsule_image_size as usize;
+        let bs = BOOT_SERVICES.get().ok_or(EfiStatus::UNSUPPORTED)?;
+        let mut pages = bs.allocate_pages(PageAllocType::AnyPages,
+                                          memory_type,
+                                          size.div_ceil(EFI_PAGE_SIZE))?;
+        unsafe { (pages.as_mut_ptr() as *mut EfiCapsuleHeader).write(*header) };
+        Ok(Capsule { pages, size })
+    }
+
+    pub fn header(&self) -> EfiCapsuleHeader {
+        unsafe { *(self.pages.as_ptr() as *const EfiCapsuleHeader) }
+    }
+
+    pub fn guid(&self) -> EfiGuid {
+        self.header().capsule_guid
+    }
+
+    pub fn flags(&self) -> UINT32 {
+        self.header().flags
+    }
+
+    pub fn persists_across_reset(&self) -> bool {
+        self.flags() & CAPSULE_FLAGS_PERSIST_ACROSS_RESET != 0
+    }
+
+    /// Physical address of the capsule header.
+    pub fn address(&self) -> EfiPhysicalAddress {
+        self.pages.address()
+    }
+
+    /// The whole image, header included.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.pages.as_slice()[..self.size]
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.as_bytes()[self.header().header_size as usize..]
+    }
+}
+
+/// Result of [`RuntimeServices::query_capsule_capabilities`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CapsuleCapabilities {
+    pub maximum_capsule_size: UINT64,
+    /// The reset the capsules need in order to be processed.
+    pub reset_type: EfiResetType,
+}
+
+fn header_array(capsules: &[&Capsule]) -> Vec<*mut EfiCapsuleHeader> {
+    capsules.iter().map(|c| c.address() as *mut EfiCapsuleHeader).collect()
+}
+
+/// One block per `(address, length)` image, as each is contiguous, followed
+/// by the all-zero terminator.
+fn block_list(images: impl Iterator<Item = (EfiPhysicalAddress, usize)>) -> Vec<EfiCapsuleBlockDescriptor> {
+    images.map(|(address, length)| EfiCapsuleBlockDescriptor { length: length as UINT64, address })
+          .chain(core::iter::once(EfiCapsuleBlockDescriptor { length: 0, address: 0 }))
+          .collect()
+}
+
+impl RuntimeServices {
+    /// Passes capsules to the firmware.
+    ///
+    /// Capsules that persist across reset are described to the firmware by a
+    /// scatter-gather list built here; on success their memory, and the
+    /// list's, is left allocated for the firmware to pick up at the next
+    /// reset. With `CAPSULE_FLAGS_INITIATE_RESET` a successful call doesn't
+    /// return.
+    pub fn update_capsule(&self, capsules: Vec<Capsule>) -> Result<(), EfiStatus> {
+        let refs: Vec<&Capsule> = capsules.iter().collect();
+        let mut headers = header_array(&refs);
+        let persist = capsules.iter().any(Capsule::persists_across_reset);
+
+        let descriptors = if persist {
+            let blocks = block_list(capsules.iter().map(|c| (c.address(), c.size)));
+            let size = blocks.len() * core::mem::size_of::<EfiCapsuleBlockDescriptor>();
+            let bs = BOOT_SERVICES.get().ok_or(EfiStatus::UNSUPPORTED)?;
+            let mut pages = bs.allocate_pages(PageAllocType::AnyPages,
+                                              EfiMemoryType::EfiRuntimeServicesData,
+                                              size.div_ceil(EFI_PAGE_SIZE))?;
+            let list = pages.as_mut_ptr() as *mut EfiCapsuleBlockDescriptor;
+            unsafe { core::ptr::copy_nonoverlapping(blocks.as_ptr(), list, blocks.len()) };
+            Some(pages)
+        } else {
+            None
+        };
+
+        let status = unsafe {
+            ((*self.services.as_ptr()).update_capsule)(
+                headers.as_mut_ptr(),
+                headers.len(),
+                descriptors.as_ref().map_or(0, PageAllocation::address),
+            )
+        };
+        efi_try!(status)?;
+
+        if let Some(descriptors) = descriptors {
+            descriptors.leak();
+            for capsule in capsules {
+                capsule.pages.leak();
+            }
+        }
+        Ok(())
+    }
+
+    /// Asks whether the firmware could process `capsules` in one
+    /// update_capsule call. A reset type outside of `EFI_RESET_TYPE` is
+    /// reported as `EfiStatus::DEVICE_ERROR`.
+    pub fn query_capsule_capabilities(&self,
+                                      capsules: &[&Capsule]
+    ) -> Result<CapsuleCapabilities, EfiStatus> {
+        let mut headers = header_array(capsules);
+        let mut maximum_capsule_size: UINT64 = 0;
+        let mut reset_type: UINT32 = 0;
+        let status = unsafe {
+            ((*self.services.as_ptr()).query_capsule_capabilities)(
+                headers.as_mut_ptr(),
+                headers.len(),
+                &mut maximum_capsule_size,
+                &mut reset_type,
+            )
+        };
+        efi_try!(status)?;
+        let reset_type = EfiResetType::try_from(reset_type).map_err(|_| EfiStatus::DEVICE_ERROR)?;
+        Ok(CapsuleCapabilities { maximum_capsule_size, reset_type })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUID: EfiGuid = EfiGuid {
+        data1: 0x6dcbd5ed,
+        data2: 0xe82d,
+        data3: 0x4c44,
+        data4: [0xbd, 0xa1, 0x71, 0x94, 0x19, 0x9a, 0xd9, 0x2a],
+    };
+
+    fn image(header_size: UINT32, flags: UINT32, payload: &[u8]) -> Vec<u8> {
+        let header = EfiCapsuleHeader {
+            capsule_guid: GUID,
+            header_size,
+            flags,
+            capsule_image_size: (HEADER_SIZE + payload.len()) as UINT32,
+        };
+        let mut bytes = alloc::vec![0u8; HEADER_SIZE];
+        unsafe { (bytes.as_mut_ptr() as *mut EfiCapsuleHeader).write_unaligned(header) };
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    #[test]
+    fn initiate_reset_without_persist_is_rejected() {
+        let result = Capsule::new(GUID, CAPSULE_FLAGS_INITIATE_RESET, &[1, 2, 3]);
+        assert_eq!(result.err(), Some(EfiStatus::INVALID_PARAMETER));
+    }
+
+    #[test]
+    fn populate_system_table_without_persist_is_rejected() {
+        let result = Capsule::new(GUID, CAPSULE_FLAGS_POPULATE_SYSTEM_TABLE, &[1, 2, 3]);
+        assert_eq!(result.err(), Some(EfiStatus::INVALID_PARAMETER));
+    }
+
+    #[test]
+    fn truncated_header_is_rejected() {
+        let bytes = image(HEADER_SIZE as UINT32, 0, &[]);
+        let result = Capsule::from_bytes(&bytes[..HEADER_SIZE - 1]);
+        assert_eq!(result.err(), Some(EfiStatus::BAD_BUFFER_SIZE));
+    }
+
+    #[test]
+    fn header_size_past_image_is_rejected() {
+        let bytes = image((HEADER_SIZE + 5) as UINT32, 0, &[1, 2, 3, 4]);
+        assert_eq!(Capsule::from_bytes(&bytes).err(), Some(EfiStatus::BAD_BUFFER_SIZE));
+    }
+
+    #[test]
+    fn header_size_below_header_is_rejected() {
+        let bytes = image(HEADER_SIZE as UINT32 - 1, 0, &[1, 2, 3, 4]);
+        assert_eq!(Capsule::from_bytes(&bytes).err(), Some(EfiStatus::BAD_BUFFER_SIZE));
+    }
+
+    #[test]
+    fn image_size_mismatch_is_rejected() {
+        let mut bytes = image(HEADER_SIZE as UINT32, 0, &[1, 2, 3, 4]);
+        bytes.push(0);
+        assert_eq!(Capsule::from_bytes(&bytes).err(), Some(EfiStatus::BAD_BUFFER_SIZE));
+    }
+
+    #[test]
+    fn valid_image_needs_boot_services() {
+        let bytes = image(HEADER_SIZE as UINT32, CAPSULE_FLAGS_PERSIST_ACROSS_RESET, &[1, 2, 3, 4]);
+        assert_eq!(Capsule::from_bytes(&bytes).err(), Some(EfiStatus::UNSUPPORTED));
+    }
+
+    #[test]
+    fn block_list_has_one_block_per_capsule_and_a_terminator() {
+        let blocks = block_list([(0x1000, 0x30), (0x5000, 0x2010)].into_iter());
+        assert_eq!(blocks, [
+            EfiCapsuleBlockDescriptor { length: 0x30, address: 0x1000 },
+            EfiCapsuleBlockDescriptor { length: 0x2010, address: 0x5000 },
+            EfiCapsuleBlockDescriptor { length: 0, address: 0 },
+        ]);
+    }
+
+    #[test]
+    fn empty_block_list_is_just_the_terminator() {
+        let blocks = block_list(core::iter::empty());
+        assert_eq!(blocks, [EfiCapsuleBlockDescriptor { length: 0, address: 0 }]);
+    }
+}
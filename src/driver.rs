@@ -0,0 +1,311 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use core::ffi::CStr;
+
+use crate::proto::component_name::*;
+use crate::proto::device_path::{DevicePathBuf, DevicePathProtocol, EfiDevicePathProtocol};
+use crate::proto::driver_binding::*;
+use crate::types::*;
+use crate::*;
+
+/// A UEFI driver model driver.
+///
+/// Installed with [`BootServices::install_driver`], which publishes the
+/// Driver Binding and Component Name 2 protocols backed by this trait.
+///
+/// The methods take `&self` because firmware may call back in while one is
+/// running, e.g. `start` connecting a child controller reaches `supported`
+/// again. State that changes needs interior mutability.
+///
+/// The protocols `start` opens `ByDriver` have to stay open until `stop`,
+/// which is how `DisconnectController` finds the driver:
+///
+/// ```ignore
+/// fn start(&self, controller: EfiHandle, _: Option<&DevicePathProtocol>) -> Result<(), EfiStatus> {
+///     let io = unsafe {
+///         BOOT_SERVICES.open_protocol::<BlockIoProtocol>(
+///             controller,
+///             self.binding_handle,
+///             OpenProtocolAttributes::ByDriver { controller },
+///         )?
+///     };
+///     self.devices.lock().push((controller, io.leak()));
+///     Ok(())
+/// }
+///
+/// fn stop(&self, controller: EfiHandle, _: &[EfiHandle]) -> Result<(), EfiStatus> {
+///     self.devices.lock().retain(|(handle, _)| *handle != controller);
+///     unsafe {
+///         BOOT_SERVICES.close_protocol::<BlockIoProtocol>(controller,
+///                                                         self.binding_handle,
+///                                                         controller)
+///     }
+/// }
+/// ```
+pub trait Driver: 'static {
+    /// Driver Binding version; higher versions are tried first.
+    const VERSION: UINT32 = 0x10;
+
+    /// Cheaply checks whether the driver can manage `controller`. Usually
+    /// opens the needed protocols `ByDriver` and closes them again.
+    fn supported(&self,
+                 controller: EfiHandle,
+                 remaining_device_path: Option<&DevicePathProtocol>
+    ) -> Result<(), EfiStatus>;
+
+    /// Starts managing `controller`. Protocols opened `ByDriver` are kept
+    /// open with [`ScopedProtocol::leak`](crate::proto::ScopedProtocol::leak).
+    fn start(&self,
+             controller: EfiHandle,
+             remaining_device_path: Option<&DevicePathProtocol>
+    ) -> Result<(), EfiStatus>;
+
+    /// Stops managing `children` of `controller`, or `controller` itself
+    /// when `children` is empty, closing what `start` opened with
+    /// [`BootServices::close_protocol`].
+    fn stop(&self,
+            controller: EfiHandle,
+            children: &[EfiHandle]
+    ) -> Result<(), EfiStatus>;
+
+    /// Semicolon-separated RFC 4646 languages the names are available in.
+    fn supported_languages(&self) -> &'static CStr {
+        c"en"
+    }
+
+    /// Null-terminated UTF-16 driver name in `language`.
+    fn driver_name(&self, language: &str) -> Option<&[CHAR16]>;
+
+    /// Null-terminated UTF-16 name of `controller`, or of its `child`.
+    fn controller_name(&self,
+                       _controller: EfiHandle,
+                       _child: Option<EfiHandle>,
+                       _language: &str
+    ) -> Option<&[CHAR16]> {
+        None
+    }
+}
+
+// both interfaces come first so either `this` pointer leads back here
+#[repr(C)]
+struct DriverInstance<D: Driver> {
+    binding: EfiDriverBindingProtocol,
+    component_name: EfiComponentName2Protocol,
+    driver: D,
+}
+
+impl<D: Driver> DriverInstance<D> {
+    // shared borrows only: the callbacks can nest
+    unsafe fn from_binding<'a>(this: *mut EfiDriverBindingProtocol) -> &'a Self {
+        unsafe { &*(this as *const Self) }
+    }
+
+    unsafe fn from_component_name<'a>(this: *mut EfiComponentName2Protocol) -> &'a Self {
+        let offset = core::mem::offset_of!(DriverInstance<D>, component_name);
+        unsafe { &*((this as *const u8).sub(offset) as *const Self) }
+    }
+}
+
+fn to_status(result: Result<(), EfiStatus>) -> EfiStatus {
+    match result {
+        Ok(()) => EfiStatus::SUCCESS,
+        Err(status) => status,
+    }
+}
+
+unsafe fn remaining_path(path: *mut EfiDevicePathProtocol) -> Option<DevicePathProtocol> {
+    (!path.is_null()).then(|| unsafe { DevicePathProtocol::new(path) })
+}
+
+unsafe fn language<'a>(language: *mut CHAR8) -> Option<&'a str> {
+    if language.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(language as *const _) }.to_str().ok()
+}
+
+fn name_ptr(name: Option<&[CHAR16]>, out: *mut *mut CHAR16) -> EfiStatus {
+    match name {
+        Some(name) if name.last() == Some(&0) => {
+            unsafe { *out = name.as_ptr() as *mut CHAR16 };
+            EfiStatus::SUCCESS
+        }
+        _ => EfiStatus::UNSUPPORTED,
+    }
+}
+
+unsafe extern "efiapi" fn binding_supported<D: Driver>(
+    this: *mut EfiDriverBindingProtocol,
+    controller_handle: EfiHandle,
+    remaining_device_path: *mut EfiDevicePathProtocol,
+) -> EfiStatus {
+    let instance = unsafe { DriverInstance::<D>::from_binding(this) };
+    let remaining = unsafe { remaining_path(remaining_device_path) };
+    to_status(instance.driver.supported(controller_handle, remaining.as_ref()))
+}
+
+unsafe extern "efiapi" fn binding_start<D: Driver>(
+    this: *mut EfiDriverBindingProtocol,
+    controller_handle: EfiHandle,
+    remaining_device_path: *mut EfiDevicePathProtocol,
+) -> EfiStatus {
+    let instance = unsafe { DriverInstance::<D>::from_binding(this) };
+    let remaining = unsafe { remaining_path(remaining_device_path) };
+    to_status(instance.driver.start(controller_handle, remaining.as_ref()))
+}
+
+unsafe extern "efiapi" fn binding_stop<D: Driver>(
+    this: *mut EfiDriverBindingProtocol,
+    controller_handle: EfiHandle,
+    number_of_children: UINTN,
+    child_handle_buffer: *mut EfiHandle,
+) -> EfiStatus {
+    let instance = unsafe { DriverInstance::<D>::from_binding(this) };
+    let children = if child_handle_buffer.is_null() {
+        &[][..]
+    } else {
+        unsafe { core::slice::from_raw_parts(child_handle_buffer, number_of_children) }
+    };
+    to_status(instance.driver.stop(controller_handle, children))
+}
+
+unsafe extern "efiapi" fn get_driver_name<D: Driver>(
+    this: *mut EfiComponentName2Protocol,
+    language_code: *mut CHAR8,
+    driver_name: *mut *mut CHAR16,
+) -> EfiStatus {
+    let instance = unsafe { DriverInstance::<D>::from_component_name(this) };
+    let (Some(lang), false) = (unsafe { language(language_code) }, driver_name.is_null()) else {
+        return EfiStatus::INVALID_PARAMETER;
+    };
+    name_ptr(instance.driver.driver_name(lang), driver_name)
+}
+
+unsafe extern "efiapi" fn get_controller_name<D: Driver>(
+    this: *mut EfiComponentName2Protocol,
+    controller_handle: EfiHandle,
+    child_handle: EfiHandle,
+    language_code: *mut CHAR8,
+    controller_name: *mut *mut CHAR16,
+) -> EfiStatus {
+    let instance = unsafe { DriverInstance::<D>::from_component_name(this) };
+    let (Some(lang), false) = (unsafe { language(language_code) }, controller_name.is_null()) else {
+        return EfiStatus::INVALID_PARAMETER;
+    };
+    let child = (!child_handle.is_null()).then_some(child_handle);
+    name_ptr(instance.driver.controller_name(controller_handle, child, lang), controller_name)
+}
+
+/// A driver installed on its image handle. Dropping it uninstalls the
+/// driver, or leaks it if firmware refuses.
+pub struct InstalledDriver<D: Driver> {
+    handle: EfiHandle,
+    instance: Option<Box<DriverInstance<D>>>,
+}
+
+impl<D: Driver> InstalledDriver<D> {
+    /// The driver binding handle, used as agent when opening protocols.
+    pub fn handle(&self) -> EfiHandle {
+        self.handle
+    }
+
+    fn interfaces(&mut self) -> [(&'static EfiGuid, *mut VOID); 2] {
+        let instance = self.instance.as_mut().unwrap();
+        [
+            (&EFI_DRIVER_BINDING_PROTOCOL_GUID, &mut instance.binding as *mut _ as *mut VOID),
+            (&EFI_COMPONENT_NAME2_PROTOCOL_GUID, &mut instance.component_name as *mut _ as *mut VOID),
+        ]
+    }
+
+    /// Uninstalls the driver and returns it, or returns `self` unchanged
+    /// if firmware refuses.
+    pub fn uninstall(mut self) -> Result<D, (Self, EfiStatus)> {
+        let interfaces = self.interfaces();
+        let result = unsafe {
+            BOOT_SERVICES.uninstall_multiple_protocol_interfaces(self.handle, &interfaces)
+        };
+        match result {
+            Ok(()) => Ok(self.instance.take().unwrap().driver),
+            Err(status) => Err((self, status)),
+        }
+    }
+}
+
+impl<D: Driver> Drop for InstalledDriver<D> {
+    fn drop(&mut self) {
+        if self.instance.is_none() {
+            return;
+        }
+        let interfaces = self.interfaces();
+        let uninstalled = BOOT_SERVICES.get().is_some_and(|bs| unsafe {
+            bs.uninstall_multiple_protocol_interfaces(self.handle, &interfaces).is_ok()
+        });
+        if !uninstalled {
+            Box::leak(self.instance.take().unwrap());
+        }
+    }
+}
+
+impl BootServices {
+    /// Publishes `driver` on `image_handle` as Driver Binding and Component
+    /// Name 2 protocols.
+    pub fn install_driver<D: Driver>(&self,
+                                     image_handle: EfiHandle,
+                                     driver: D
+    ) -> Result<InstalledDriver<D>, EfiStatus> {
+        let languages = driver.supported_languages().as_ptr() as *mut CHAR8;
+        let instance = Box::new(DriverInstance {
+            binding: EfiDriverBindingProtocol {
+                supported: binding_supported::<D>,
+                start: binding_start::<D>,
+                stop: binding_stop::<D>,
+                version: D::VERSION,
+                image_handle,
+                driver_binding_handle: image_handle,
+            },
+            component_name: EfiComponentName2Protocol {
+                get_driver_name: get_driver_name::<D>,
+                get_controller_name: get_controller_name::<D>,
+                supported_languages: languages,
+            },
+            driver,
+        });
+
+        let mut installed = InstalledDriver { handle: image_handle, instance: Some(instance) };
+        let interfaces = installed.interfaces();
+        let result = unsafe {
+            self.install_multiple_protocol_interfaces(Some(image_handle), &interfaces)
+        };
+        match result {
+            Ok(_) => Ok(installed),
+            Err(status) => {
+                // nothing was installed, so the instance can simply be freed
+                drop(installed.instance.take());
+                Err(status)
+            }
+        }
+    }
+
+    /// Connects drivers to `controller`. `drivers` restricts and orders the
+    /// candidates; `remaining_device_path` limits which children are made.
+    ///
+    /// # Safety
+    ///
+    /// `controller` and each handle in `drivers` must be live.
+    pub unsafe fn connect_controller(&self,
+                                     controller: EfiHandle,
+                                     drivers: Option<&[EfiHandle]>,
+                                     remaining_device_path: Option<&DevicePathBuf>,
+                                     recursive: bool
+    ) -> Result<(), EfiStatus> {
+        let mut driver_list: Option<Vec<EfiHandle>> = drivers.map(|drivers| {
+            drivers.iter().copied().chain(core::iter::once(core::ptr::null_mut())).collect()
+        });
+        let driver_ptr = driver_list.as_mut().map_or(core::ptr::null_mut(), |d| d.as_mut_ptr());
+        let path_ptr = remaining_device_path.map_or(core::ptr::null_mut(), |p| p.as_ptr());
+        unsafe {
+            self.connect_controller_raw(controller, driver_ptr, path_ptr, recursive)
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use core::time::Duration;
+
+use crate::types::*;
+use crate::*;
+
+type NotifyFn = dyn FnMut(EfiEvent);
+
+/// Owned firmware event, closed on drop.
+///
+/// A notify closure attached with [`Event::with_notify`] lives as long as
+/// the event and is released only after the event has been closed.
+pub struct Event {
+    event: EfiEvent,
+    notify: Option<Box<NotifyFn>>,
+}
+
+unsafe extern "efiapi" fn notify_trampoline<F: FnMut(EfiEvent)>(
+    event: EfiEvent,
+    context: *mut VOID,
+) {
+    // SAFETY: `context` is the boxed closure owned by the `Event`, which
+    // closes the event before releasing it
+    let notify = unsafe { &mut *(context as *mut F) };
+    notify(event);
+}
+
+impl Event {
+    /// Creates an event without a notify function, e.g. a plain `EVT_TIMER`.
+    pub fn new(event_type: UINT32) -> Result<Self, EfiStatus> {
+        let event = unsafe {
+            BOOT_SERVICES.create_event(event_type, 0, None, core::ptr::null_mut())?
+        };
+        Ok(Event { event, notify: None })
+    }
+
+    /// Creates a timer event to be used with [`Event::wait`] or
+    /// [`Event::check`].
+    pub fn timer() -> Result<Self, EfiStatus> {
+        Event::new(EVT_TIMER)
+    }
+
+    /// Creates an event whose notify function is the closure `notify`,
+    /// invoked by firmware at `notify_tpl`.
+    ///
+    /// `event_type` must include `EVT_NOTIFY_WAIT` or `EVT_NOTIFY_SIGNAL`.
+    pub fn with_notify<F>(event_type: UINT32,
+                          notify_tpl: EfiTpl,
+                          notify: F
+    ) -> Result<Self, EfiStatus>
+    where
+        F: FnMut(EfiEvent) + 'static,
+    {
+        Event::with_notify_ex(event_type, notify_tpl, None, notify)
+    }
+
+    /// Like [`Event::with_notify`], optionally placing the event in
+    /// `event_group`.
+    pub fn with_notify_ex<F>(event_type: UINT32,
+                             notify_tpl: EfiTpl,
+                             event_group: Option<&EfiGuid>,
+                             notify: F
+    ) -> Result<Self, EfiStatus>
+    where
+        F: FnMut(EfiEvent) + 'static,
+    {
+        let mut boxed = Box::new(notify);
+        let context = &mut *boxed as *mut F as *mut VOID;
+        let event = unsafe {
+            BOOT_SERVICES.create_event_ex(
+                event_type,
+                notify_tpl,
+                Some(notify_trampoline::<F>),
+                context,
+                event_group,
+            )?
+        };
+        Ok(Event { event, notify: Some(boxed) })
+    }
+
+    /// Creates an event with a raw notify function and context pointer.
+    ///
+    /// # Safety
+    ///
+    /// `notify_context` must stay valid, and be safe to use from
+    /// `notify_function`, for as long as the event exists.
+    pub unsafe fn with_raw_notify(event_type: UINT32,
+                                  notify_tpl: EfiTpl,
+                                  notify_function: EfiEventNotify,
+                                  notify_context: *mut VOID
+    ) -> Result<Self, EfiStatus> {
+        let event = unsafe {
+            BOOT_SERVICES.create_event(
+                event_type,
+                notify_tpl,
+                Some(notify_function),
+                notify_context,
+            )?
+        };
+        Ok(Event { event, notify: None })
+    }
+
+    /// Takes ownership of an event created elsewhere.
+    ///
+    /// # Safety
+    ///
+    /// `event` must be a valid event that nothing else closes.
+    pub unsafe fn from_raw(event: EfiEvent) -> Self {
+        Event { event, notify: None }
+    }
+
+    pub fn as_raw(&self) -> EfiEvent {
+        self.event
+    }
+
+    /// Fires once after `delay`.
+    pub fn set_relative(&self, delay: Duration) -> Result<(), EfiStatus> {
+        // SAFETY: `self` owns the open event
+        unsafe {
+            BOOT_SERVICES.set_timer(
+                self.event,
+                EfiTimerDelay::TimerRelative,
+                timer_units(delay),
+            )
+        }
+    }
+
+    /// Fires every `period`; a zero period fires on every timer tick.
+    pub fn set_periodic(&self, period: Duration) -> Result<(), EfiStatus> {
+        // SAFETY: `self` owns the open event
+        unsafe {
+            BOOT_SERVICES.set_timer(
+                self.event,
+                EfiTimerDelay::TimerPeriodic,
+                timer_units(period),
+            )
+        }
+    }
+
+    pub fn cancel_timer(&self) -> Result<(), EfiStatus> {
+        // SAFETY: `self` owns the open event
+        unsafe { BOOT_SERVICES.set_timer(self.event, EfiTimerDelay::TimerCancel, 0) }
+    }
+
+    pub fn signal(&self) -> Result<(), EfiStatus> {
+        // SAFETY: `self` owns the open event
+        unsafe { BOOT_SERVICES.signal_event(self.event) }
+    }
+
+    /// Returns whether the event was signaled, clearing the signal.
+    pub fn check(&self) -> Result<bool, EfiStatus> {
+        // SAFETY: `self` owns the open event
+        unsafe { BOOT_SERVICES.check_event(self.event) }
+    }
+
+    /// Blocks until this event is signaled.
+    pub fn wait(&self) -> Result<(), EfiStatus> {
+        Event::wait_any(&[self]).map(|_| ())
+    }
+
+    /// Blocks until one of `events` is signaled and returns its index.
+    pub fn wait_any(events: &[&Event]) -> Result<usize, EfiStatus> {
+        let mut raw: Vec<EfiEvent> = events.iter().map(|e| e.event).collect();
+        BOOT_SERVICES.wait_for_event(&mut raw)
+    }
+}
+
+impl Drop for Event {
+    fn drop(&mut self) {
+        // events are torn down along with boot services
+        if let Some(bs) = BOOT_SERVICES.get() {
+            let _ = unsafe { bs.close_event(self.event) };
+        }
+        drop(self.notify.take());
+    }
+}
+
+// timer services count in 100ns units
+fn timer_units(duration: Duration) -> UINT64 {
+    (duration.as_nanos() / 100).min(UINT64::MAX as u128) as UINT64
+}
@@ -0,0 +1,85 @@
+use alloc::vec::Vec;
+
+use core::ptr::NonNull;
+
+use crate::proto::Protocol;
+use crate::types::*;
+use crate::*;
+
+/// Pool-allocated handle array from `locate_handle_buffer`, freed on drop.
+pub struct HandleBuffer {
+    buffer: Option<NonNull<EfiHandle>>,
+    count: usize,
+}
+
+impl HandleBuffer {
+    pub fn handles(&self) -> &[EfiHandle] {
+        match self.buffer {
+            // SAFETY: firmware returned `count` handles at `buffer`
+            Some(buffer) => unsafe {
+                core::slice::from_raw_parts(buffer.as_ptr(), self.count)
+            },
+            None => &[],
+        }
+    }
+}
+
+impl core::ops::Deref for HandleBuffer {
+    type Target = [EfiHandle];
+
+    fn deref(&self) -> &[EfiHandle] {
+        self.handles()
+    }
+}
+
+impl Drop for HandleBuffer {
+    fn drop(&mut self) {
+        if let (Some(buffer), Some(bs)) = (self.buffer, BOOT_SERVICES.get()) {
+            let _ = bs.free_pool(buffer.cast());
+        }
+    }
+}
+
+impl BootServices {
+    /// Returns every handle that supports `P`; empty if there are none.
+    pub fn find_handles<P: Protocol>(&self) -> Result<HandleBuffer, EfiStatus> {
+        self.locate_handles(EfiLocateSearchType::ByProtocol, Some(&P::GUID))
+    }
+
+    /// Returns every handle in the handle database.
+    pub fn all_handles(&self) -> Result<HandleBuffer, EfiStatus> {
+        self.locate_handles(EfiLocateSearchType::AllHandles, None)
+    }
+
+    fn locate_handles(&self,
+                      search_type: EfiLocateSearchType,
+                      protocol: Option<&EfiGuid>
+    ) -> Result<HandleBuffer, EfiStatus> {
+        // SAFETY: no search key
+        match unsafe { self.locate_handle_buffer(search_type, protocol, core::ptr::null_mut()) } {
+            Ok((buffer, count)) => Ok(HandleBuffer { buffer: Some(buffer), count }),
+            Err(EfiStatus::NOT_FOUND) => Ok(HandleBuffer { buffer: None, count: 0 }),
+            Err(status) => Err(status),
+        }
+    }
+
+    /// Returns the GUIDs of all protocols installed on `handle`.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a live handle.
+    pub unsafe fn protocols_per_handle(&self,
+                                       handle: EfiHandle
+    ) -> Result<Vec<EfiGuid>, EfiStatus> {
+        let (buffer, count) = unsafe { self.protocols_per_handle_raw(handle)? };
+        // SAFETY: firmware returned `count` valid GUID pointers at `buffer`
+        let guids = unsafe {
+            core::slice::from_raw_parts(buffer.as_ptr(), count)
+                .iter()
+                .map(|&guid| *guid)
+                .collect()
+        };
+        let _ = self.free_pool(buffer.cast());
+        Ok(guids)
+    }
+}
@@ -0,0 +1,220 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use core::ptr::NonNull;
+
+use crate::proto::OpenProtocolAttributes;
+use crate::proto::device_path::{DevicePathBuf, DevicePathProtocol};
+use crate::proto::loaded_image::LoadedImageProtocol;
+use crate::types::*;
+use crate::*;
+
+/// Result of running an image with [`BootServices::start_image`].
+#[derive(Debug, Clone)]
+pub struct ExitData {
+    /// Status the image exited with, or the error from `start_image`.
+    pub status: EfiStatus,
+    /// Exit data: a null-terminated UTF-16 string, optionally followed by
+    /// binary data.
+    pub data: Vec<u16>,
+}
+
+impl ExitData {
+    /// The string part of the exit data.
+    pub fn description(&self) -> String {
+        let end = self.data.iter().position(|&c| c == 0).unwrap_or(self.data.len());
+        String::from_utf16_lossy(&self.data[..end])
+    }
+}
+
+impl BootServices {
+    /// Loads an image from a device path. With `boot_policy` set, the path
+    /// may name a boot option rather than a file.
+    ///
+    /// # Safety
+    ///
+    /// `parent_image_handle` must be the handle of a live image.
+    pub unsafe fn load_image_from_path(&self,
+                                       parent_image_handle: EfiHandle,
+                                       device_path: &DevicePathBuf,
+                                       boot_policy: bool
+    ) -> Result<EfiHandle, EfiStatus> {
+        unsafe {
+            self.load_image_raw(
+                boot_policy as BOOLEAN,
+                parent_image_handle,
+                device_path.as_ptr(),
+                core::ptr::null_mut(),
+                0,
+            )
+        }
+    }
+
+    /// Loads an image from memory. `device_path`, if given, is recorded as
+    /// the image's origin.
+    ///
+    /// # Safety
+    ///
+    /// `parent_image_handle` must be the handle of a live image.
+    pub unsafe fn load_image_from_buffer(&self,
+                                         parent_image_handle: EfiHandle,
+                                         buffer: &[u8],
+                                         device_path: Option<&DevicePathBuf>
+    ) -> Result<EfiHandle, EfiStatus> {
+        let path = device_path.map_or(core::ptr::null_mut(), |p| p.as_ptr());
+        unsafe {
+            self.load_image_raw(
+                FALSE,
+                parent_image_handle,
+                path,
+                buffer.as_ptr() as *mut VOID,
+                buffer.len(),
+            )
+        }
+    }
+
+    /// Transfers control to a loaded image and returns once it exits.
+    ///
+    /// # Safety
+    ///
+    /// `image_handle` must come from a `load_image_*` call and must not have
+    /// been started or unloaded. The image runs with full access to the machine
+    /// and must not free anything this image still uses.
+    pub unsafe fn start_image(&self, image_handle: EfiHandle) -> ExitData {
+        let mut exit_data_size: UINTN = 0;
+        let mut exit_data: *mut CHAR16 = core::ptr::null_mut();
+        let status = unsafe {
+            ((*self.services.as_ptr()).start_image)(
+                image_handle,
+                &mut exit_data_size,
+                &mut exit_data,
+            )
+        };
+
+        let mut data = Vec::new();
+        if let Some(ptr) = NonNull::new(exit_data) {
+            // SAFETY: firmware hands us `exit_data_size` bytes of pool memory
+            let chars = unsafe {
+                core::slice::from_raw_parts(
+                    ptr.as_ptr(),
+                    exit_data_size / core::mem::size_of::<CHAR16>(),
+                )
+            };
+            data.extend_from_slice(chars);
+            let _ = self.free_pool(ptr.cast());
+        }
+        ExitData { status, data }
+    }
+
+    /// Exits `image_handle` with `exit_status` and an optional message.
+    ///
+    /// For the running image this only returns if firmware rejects the
+    /// call; for an image that was loaded but not started it unloads it.
+    ///
+    /// # Safety
+    ///
+    /// `image_handle` must be a live image. For the running image a successful
+    /// call doesn't return, so nothing on the stack is dropped. Any other image
+    /// is unloaded, and nothing it provided may be used afterwards.
+    pub unsafe fn exit(&self,
+                       image_handle: EfiHandle,
+                       exit_status: EfiStatus,
+                       exit_data: Option<&str>
+    ) -> Result<(), EfiStatus> {
+        // the caller of start_image frees the exit data, so it has to come
+        // from pool
+        let (size, data) = match exit_data {
+            Some(text) => {
+                let chars: Vec<u16> = text.encode_utf16()
+                                          .chain(core::iter::once(0))
+                                          .collect();
+                let size = core::mem::size_of_val(chars.as_slice());
+                let buffer = self.allocate_pool(EfiMemoryType::EfiLoaderData, size)?;
+                unsafe {
+                    core::ptr::copy_nonoverlapping(
+                        chars.as_ptr(),
+                        buffer.as_ptr() as *mut CHAR16,
+                        chars.len(),
+                    );
+                }
+                (size, buffer.as_ptr() as *mut CHAR16)
+            }
+            None => (0, core::ptr::null_mut()),
+        };
+
+        let status = unsafe {
+            ((*self.services.as_ptr()).exit)(
+                image_handle,
+                exit_status,
+                size,
+                data,
+            )
+        };
+        if let Some(data) = NonNull::new(data) {
+            let _ = self.free_pool(data.cast());
+        }
+        efi_try!(status)
+    }
+
+    /// Loads and starts `path`, resolved on the device the running image
+    /// was loaded from, e.g. `\EFI\BOOT\shimx64.efi`.
+    ///
+    /// `load_options` is passed to the new image as its command line.
+    /// Errors while loading are returned as `Err`; once the image runs, its
+    /// exit status is reported in the returned [`ExitData`].
+    ///
+    /// # Safety
+    ///
+    /// `image_handle` must be the handle of the running image, and the image
+    /// at `path` is trusted as for [`BootServices::start_image`].
+    pub unsafe fn chainload(&self,
+                            image_handle: EfiHandle,
+                            path: &str,
+                            load_options: Option<&str>
+    ) -> Result<ExitData, EfiStatus> {
+        let device_path = {
+            let loaded = unsafe {
+                self.open_protocol::<LoadedImageProtocol>(image_handle,
+                                                          image_handle,
+                                                          OpenProtocolAttributes::GetProtocol)?
+            };
+            let device = unsafe {
+                self.open_protocol::<DevicePathProtocol>(loaded.device_handle(),
+                                                         image_handle,
+                                                         OpenProtocolAttributes::GetProtocol)?
+            };
+            let mut path_buf = DevicePathBuf::from(&*device);
+            path_buf.push_file_path(path)?;
+            path_buf
+        };
+
+        let child = unsafe { self.load_image_from_path(image_handle, &device_path, false)? };
+
+        let options: Vec<u8> = load_options
+            .map(|opts| {
+                opts.encode_utf16()
+                    .chain(core::iter::once(0))
+                    .flat_map(|c| c.to_le_bytes())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if !options.is_empty() {
+            let child_image = unsafe {
+                self.open_protocol::<LoadedImageProtocol>(child,
+                                                          image_handle,
+                                                          OpenProtocolAttributes::GetProtocol)
+            };
+            match child_image {
+                // SAFETY: `options` outlives start_image below
+                Ok(child_image) => unsafe { child_image.set_load_options(&options) },
+                Err(status) => {
+                    let _ = unsafe { self.unload_image(child) };
+                    return Err(status);
+                }
+            }
+        }
+
+        // SAFETY: `child` was just loaded
+        Ok(unsafe { self.start_image(child) })
+    }
+}
@@ -1,5 +1,6 @@
 #![no_std]
-#![feature(allocator_api)]
+
+extern crate alloc;
 
 pub mod types;
 pub mod proto;
@@ -7,6 +8,24 @@ pub mod tables;
 pub mod allocator;
 pub mod safeptr;
 pub mod macros;
+pub mod memory;
+pub mod event;
+pub mod tpl;
+pub mod handle;
+pub mod image;
+pub mod driver;
+pub mod notify;
+pub mod variable;
+pub mod boot_manager;
+pub mod secure_boot;
+pub mod auth_variable;
+pub mod time;
+pub mod reset;
+pub mod capsule;
+pub mod runtime;
+pub mod acpi;
+pub mod smbios;
+mod bytes;
 
 use spin::Once;
 
@@ -18,6 +37,7 @@ use crate::tables::rs::*;
 use crate::tables::bs::*;
 use crate::safeptr::*;
 
+#[cfg(feature = "panic-handler")]
 use core::panic::PanicInfo;
 
 #[derive(Debug)]
@@ -28,28 +48,77 @@ pub enum EfiInitError {
 
 pub struct EfiGlobal<T> {
     inner: Once<T>,
+    poisoned: AtomicBool,
 }
 
 impl<T> EfiGlobal<T> {
     pub const fn new() -> Self {
         Self {
-            inner: Once::new()
+            inner: Once::new(),
+            poisoned: AtomicBool::new(false),
         }
     }
 
     fn init(&self, val: T) {
         self.inner.call_once(|| val);
     }
+
+    /// Returns `None` before `init_efilib` and after the global is poisoned.
+    ///
+    /// Poisoning only guards access through the global. A reference taken
+    /// from it earlier stays usable to the compiler, so it must not outlive
+    /// the phase the global belongs to.
+    pub fn get(&self) -> Option<&T> {
+        if self.is_poisoned() {
+            return None;
+        }
+        self.inner.get()
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::SeqCst)
+    }
+
+    fn poison(&self) {
+        self.poisoned.store(true, Ordering::SeqCst);
+    }
 }
 
+impl<T> Default for EfiGlobal<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Panics before `init_efilib` and after the global is poisoned; use
+/// [`EfiGlobal::get`] where that can happen.
 impl<T> core::ops::Deref for EfiGlobal<T> {
     type Target = T;
 
     fn deref(&self) -> &T {
+        if self.is_poisoned() {
+            panic!("EfiLib global used after exit_boot_services");
+        }
         self.inner.get().expect("EfiLib not initialized")
     }
 }
 
+/// Whether `BootServices::exit_boot_services` has completed.
+pub fn boot_services_exited() -> bool {
+    BOOT_SERVICES.is_poisoned()
+}
+
+/// Marks boot-time globals unusable once the firmware has torn them down.
+pub(crate) fn poison_boot_services() {
+    BOOT_SERVICES.poison();
+    if let Some(st) = SYSTEM_TABLE.get() {
+        st.clear_boot_time_fields();
+    }
+}
+
+/// # Safety
+///
+/// `system_table` must be the table passed to the image entry point.
 pub unsafe fn init_efilib(system_table: *mut EfiSystemTable
 ) -> Result<(), EfiInitError> {
     if INITIALIZED.load(Ordering::SeqCst) {
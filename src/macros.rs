@@ -1,31 +1,32 @@
 #[macro_export]
 macro_rules! system_table {
     () => {
-        $crate::SYSTEM_TABLE.get().expect("EfiLib not initialized")
+        &*$crate::SYSTEM_TABLE
     };
 }
 
 #[macro_export]
 macro_rules! boot_services {
     () => {
-        $crate::BOOT_SERVICES.get().expect("EfiLib not initialized")
+        &*$crate::BOOT_SERVICES
     };
 }
 
 #[macro_export]
 macro_rules! runtime_services {
     () => {
-        $crate::RUNTIME_SERVICES.get().expect("EfiLib not initialized")
+        &*$crate::RUNTIME_SERVICES
     };
 }
 
 #[macro_export]
 macro_rules! efi_try {
-    ($status:expr) => {
-        if $status.is_success() {
+    ($status:expr) => {{
+        let status: $crate::types::EfiStatus = $status;
+        if status.is_success() {
             Ok(())
         } else {
-            Err($status)
+            Err(status)
         }
-    };
+    }};
 }
@@ -0,0 +1,440 @@
+use alloc::vec::Vec;
+
+use crate::types::*;
+use crate::tables::bs::BootServices;
+use crate::*;
+
+/// Placement strategy for [`BootServices::allocate_pages`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PageAllocType {
+    /// Any available range.
+    AnyPages,
+    /// Any range ending at or below the given address.
+    MaxAddress(EfiPhysicalAddress),
+    /// Exactly the range starting at the given address.
+    Address(EfiPhysicalAddress),
+}
+
+/// Owned range of pages from [`BootServices::allocate_pages`].
+///
+/// The pages are returned to the firmware when the guard is dropped, unless
+/// ownership is given up with [`PageAllocation::leak`].
+pub struct PageAllocation {
+    address: EfiPhysicalAddress,
+    pages: UINTN,
+}
+
+impl PageAllocation {
+    /// # Safety
+    ///
+    /// `address` and `pages` must describe a live `allocate_pages` range that
+    /// nothing else frees.
+    pub unsafe fn from_raw(address: EfiPhysicalAddress, pages: UINTN) -> Self {
+        PageAllocation { address, pages }
+    }
+
+    pub fn address(&self) -> EfiPhysicalAddress {
+        self.address
+    }
+
+    pub fn pages(&self) -> UINTN {
+        self.pages
+    }
+
+    /// Size of the range in bytes.
+    pub fn size(&self) -> usize {
+        self.pages * EFI_PAGE_SIZE
+    }
+
+    pub fn as_ptr(&self) -> *const u8 {
+        self.address as *const u8
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.address as *mut u8
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.as_ptr(), self.size()) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.as_mut_ptr(), self.size()) }
+    }
+
+    /// Gives up ownership without freeing, e.g. for memory handed to a
+    /// kernel. Returns the physical address of the range.
+    pub fn leak(self) -> EfiPhysicalAddress {
+        let address = self.address;
+        core::mem::forget(self);
+        address
+    }
+}
+
+impl Drop for PageAllocation {
+    fn drop(&mut self) {
+        // pages still held at exit_boot_services belong to the caller now
+        if let Some(bs) = BOOT_SERVICES.get() {
+            let _ = unsafe { bs.free_pages(self.address, self.pages) };
+        }
+    }
+}
+
+/// Snapshot of the firmware memory map.
+///
+/// Descriptors are laid out `descriptor_size` bytes apart, which may be more
+/// than `size_of::<EfiMemoryDescriptor>()`; all accessors honor that stride.
+pub struct MemoryMap {
+    buffer: Vec<u64>,
+    map_size: UINTN,
+    required_size: UINTN,
+    map_key: UINTN,
+    descriptor_size: UINTN,
+    descriptor_version: UINT32,
+}
+
+impl MemoryMap {
+    // growing the buffer may itself split a free range, so leave room for a
+    // few more descriptors than the firmware asked for
+    const SLACK_DESCRIPTORS: usize = 4;
+
+    pub(crate) fn new() -> Self {
+        MemoryMap {
+            buffer: Vec::new(),
+            map_size: 0,
+            required_size: 0,
+            map_key: 0,
+            descriptor_size: core::mem::size_of::<EfiMemoryDescriptor>(),
+            descriptor_version: 0,
+        }
+    }
+
+    /// Refills the map from firmware without allocating. On
+    /// `EfiStatus::BUFFER_TOO_SMALL` the required size is kept for `grow`.
+    pub(crate) fn fetch(&mut self, bs: &BootServices) -> Result<(), EfiStatus> {
+        let mut map_size = self.buffer.len() * core::mem::size_of::<u64>();
+        let mut descriptor_size: UINTN = 0;
+        let status = unsafe {
+            ((*bs.services.as_ptr()).get_memory_map)(
+                &mut map_size,
+                self.buffer.as_mut_ptr() as *mut EfiMemoryDescriptor,
+                &mut self.map_key,
+                &mut descriptor_size,
+                &mut self.descriptor_version,
+            )
+        };
+        if descriptor_size != 0 {
+            self.descriptor_size = descriptor_size;
+        }
+
+        match status {
+            EfiStatus::SUCCESS => {
+                let stride_ok = self.descriptor_size >= core::mem::size_of::<EfiMemoryDescriptor>()
+                    && self.descriptor_size.is_multiple_of(core::mem::align_of::<EfiMemoryDescriptor>());
+                if !stride_ok {
+                    self.map_size = 0;
+                    return Err(EfiStatus::INCOMPATIBLE_VERSION);
+                }
+                self.map_size = map_size;
+            }
+            EfiStatus::BUFFER_TOO_SMALL => {
+                self.required_size = map_size;
+                self.map_size = 0;
+            }
+            _ => self.map_size = 0,
+        }
+        efi_try!(status)
+    }
+
+    /// Resizes the buffer to the size requested by the last `fetch`, plus
+    /// slack.
+    pub(crate) fn grow(&mut self) {
+        let bytes = self.required_size + Self::SLACK_DESCRIPTORS * self.descriptor_size;
+        self.buffer.resize(bytes.div_ceil(core::mem::size_of::<u64>()), 0);
+    }
+
+    pub fn map_key(&self) -> UINTN {
+        self.map_key
+    }
+
+    pub fn descriptor_size(&self) -> UINTN {
+        self.descriptor_size
+    }
+
+    pub fn descriptor_version(&self) -> UINT32 {
+        self.descriptor_version
+    }
+
+    /// Size in bytes of the descriptor array, as passed to firmware.
+    pub fn map_size(&self) -> UINTN {
+        self.map_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.map_size / self.descriptor_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_ptr(&self) -> *const EfiMemoryDescriptor {
+        self.buffer.as_ptr() as *const EfiMemoryDescriptor
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut EfiMemoryDescriptor {
+        self.buffer.as_mut_ptr() as *mut EfiMemoryDescriptor
+    }
+
+    pub fn get(&self, index: usize) -> Option<&EfiMemoryDescriptor> {
+        if index >= self.len() {
+            return None;
+        }
+        // SAFETY: in bounds, and `fetch` checked size and alignment of the stride
+        unsafe {
+            let ptr = (self.as_ptr() as *const u8).add(index * self.descriptor_size);
+            Some(&*(ptr as *const EfiMemoryDescriptor))
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut EfiMemoryDescriptor> {
+        if index >= self.len() {
+            return None;
+        }
+        // SAFETY: see `get`
+        unsafe {
+            let ptr = (self.as_mut_ptr() as *mut u8).add(index * self.descriptor_size);
+            Some(&mut *(ptr as *mut EfiMemoryDescriptor))
+        }
+    }
+
+    pub fn iter(&self) -> MemoryMapIter<'_> {
+        MemoryMapIter { map: self, index: 0 }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut EfiMemoryDescriptor> {
+        let stride = self.descriptor_size;
+        let base = self.as_mut_ptr() as *mut u8;
+        // SAFETY: each index yields a distinct, non-overlapping descriptor
+        (0..self.len()).map(move |i| unsafe {
+            &mut *(base.add(i * stride) as *mut EfiMemoryDescriptor)
+        })
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        let stride = self.descriptor_size;
+        let base = self.as_mut_ptr() as *mut u8;
+        // SAFETY: both indices are in bounds and distinct entries don't overlap
+        unsafe {
+            core::ptr::swap_nonoverlapping(
+                base.add(a * stride),
+                base.add(b * stride),
+                stride,
+            );
+        }
+    }
+
+    /// Sorts descriptors by physical start address.
+    pub fn sort(&mut self) {
+        // maps are a few hundred entries at most and usually close to sorted
+        for i in 1..self.len() {
+            let mut j = i;
+            while j > 0 && self.get(j - 1).unwrap().physical_start
+                > self.get(j).unwrap().physical_start
+            {
+                self.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+    }
+
+    /// Sorts the map and coalesces physically contiguous descriptors that
+    /// share type and attributes.
+    ///
+    /// The result is meant for inspection; don't hand a merged map back to
+    /// `set_virtual_address_map`.
+    pub fn merge_adjacent(&mut self) {
+        self.sort();
+        if self.is_empty() {
+            return;
+        }
+
+        let mut last = 0;
+        for i in 1..self.len() {
+            let next = *self.get(i).unwrap();
+            let prev = self.get_mut(last).unwrap();
+            // a range running past the end of the address space is left alone
+            let prev_end = prev.number_of_pages
+                .checked_mul(EFI_PAGE_SIZE as u64)
+                .and_then(|size| prev.physical_start.checked_add(size));
+            if prev.mem_type == next.mem_type
+                && prev.attribute == next.attribute
+                && prev_end == Some(next.physical_start)
+                && let Some(pages) = prev.number_of_pages.checked_add(next.number_of_pages)
+            {
+                prev.number_of_pages = pages;
+            } else {
+                last += 1;
+                if last != i {
+                    *self.get_mut(last).unwrap() = next;
+                }
+            }
+        }
+        self.map_size = (last + 1) * self.descriptor_size;
+    }
+
+    /// Total pages of the given memory type.
+    pub fn total_pages(&self, mem_type: UINT32) -> UINT64 {
+        self.iter()
+            .filter(|d| d.mem_type == mem_type)
+            .map(|d| d.number_of_pages)
+            .sum()
+    }
+
+    /// Page totals per memory type, ordered by type.
+    pub fn page_totals(&self) -> Vec<(UINT32, UINT64)> {
+        let mut totals: Vec<(UINT32, UINT64)> = Vec::new();
+        for desc in self.iter() {
+            match totals.binary_search_by_key(&desc.mem_type, |&(t, _)| t) {
+                Ok(i) => totals[i].1 += desc.number_of_pages,
+                Err(i) => totals.insert(i, (desc.mem_type, desc.number_of_pages)),
+            }
+        }
+        totals
+    }
+}
+
+impl<'a> IntoIterator for &'a MemoryMap {
+    type Item = &'a EfiMemoryDescriptor;
+    type IntoIter = MemoryMapIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct MemoryMapIter<'a> {
+    map: &'a MemoryMap,
+    index: usize,
+}
+
+impl<'a> Iterator for MemoryMapIter<'a> {
+    type Item = &'a EfiMemoryDescriptor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let desc = self.map.get(self.index)?;
+        self.index += 1;
+        Some(desc)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.map.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for MemoryMapIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOADER: UINT32 = EfiMemoryType::EfiLoaderData as UINT32;
+    const FREE: UINT32 = EfiMemoryType::EfiConventionalMemory as UINT32;
+
+    // firmware commonly reports 48-byte descriptors, 8 bytes past ours
+    const STRIDE: usize = 48;
+    const PADDING: u64 = 0x5a5a_5a5a_5a5a_5a5a;
+
+    fn map(descs: &[(UINT32, u64, u64)]) -> MemoryMap {
+        let words = STRIDE / 8;
+        let mut buffer = alloc::vec![PADDING; descs.len() * words];
+        for (i, &(mem_type, start, pages)) in descs.iter().enumerate() {
+            let desc = EfiMemoryDescriptor {
+                mem_type,
+                physical_start: start,
+                virtual_start: 0,
+                number_of_pages: pages,
+                attribute: EFI_MEMORY_WB,
+            };
+            unsafe { (buffer.as_mut_ptr().add(i * words) as *mut EfiMemoryDescriptor).write(desc) };
+        }
+        MemoryMap {
+            buffer,
+            map_size: descs.len() * STRIDE,
+            required_size: 0,
+            map_key: 0,
+            descriptor_size: STRIDE,
+            descriptor_version: 1,
+        }
+    }
+
+    fn starts(map: &MemoryMap) -> Vec<u64> {
+        map.iter().map(|d| d.physical_start).collect()
+    }
+
+    #[test]
+    fn iter_uses_descriptor_stride() {
+        let map = map(&[(LOADER, 0x1000, 1), (FREE, 0x2000, 2), (FREE, 0x4000, 3)]);
+        assert_eq!(map.len(), 3);
+        assert_eq!(starts(&map), [0x1000, 0x2000, 0x4000]);
+        assert_eq!(map.iter().map(|d| d.number_of_pages).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(map.iter().len(), 3);
+        assert!(map.get(3).is_none());
+    }
+
+    #[test]
+    fn iter_mut_leaves_padding_alone() {
+        let mut map = map(&[(LOADER, 0x1000, 1), (FREE, 0x2000, 2)]);
+        for desc in map.iter_mut() {
+            desc.virtual_start = desc.physical_start + 0x8000;
+        }
+        assert_eq!(map.get(1).unwrap().virtual_start, 0xa000);
+        assert_eq!(map.buffer[5], PADDING);
+        assert_eq!(map.buffer[11], PADDING);
+    }
+
+    #[test]
+    fn sort_by_physical_start() {
+        let mut map = map(&[(FREE, 0x4000, 3), (LOADER, 0x1000, 1), (FREE, 0x2000, 2)]);
+        map.sort();
+        assert_eq!(starts(&map), [0x1000, 0x2000, 0x4000]);
+        assert_eq!(map.get(0).unwrap().mem_type, LOADER);
+        assert_eq!(map.get(2).unwrap().number_of_pages, 3);
+    }
+
+    #[test]
+    fn merge_adjacent_coalesces_contiguous_ranges() {
+        let mut map = map(&[(FREE, 0x3000, 1), (FREE, 0x1000, 2), (FREE, 0x4000, 4)]);
+        map.merge_adjacent();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.map_size(), STRIDE);
+        assert_eq!(map.get(0).unwrap().physical_start, 0x1000);
+        assert_eq!(map.get(0).unwrap().number_of_pages, 7);
+    }
+
+    #[test]
+    fn merge_adjacent_keeps_gaps_and_type_changes() {
+        let mut map = map(&[(FREE, 0x1000, 1), (LOADER, 0x2000, 1), (FREE, 0x3000, 1),
+                            (FREE, 0x5000, 1)]);
+        map.merge_adjacent();
+        assert_eq!(starts(&map), [0x1000, 0x2000, 0x3000, 0x5000]);
+    }
+
+    #[test]
+    fn merge_adjacent_skips_overflowing_end() {
+        // the first range claims to run past the end of the address space
+        let mut map = map(&[(FREE, 0x1000, u64::MAX / 0x1000), (FREE, 0x2000, 1)]);
+        map.merge_adjacent();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(1).unwrap().number_of_pages, 1);
+    }
+
+    #[test]
+    fn page_totals_per_type() {
+        let map = map(&[(FREE, 0x1000, 2), (LOADER, 0x3000, 1), (FREE, 0x8000, 5)]);
+        assert_eq!(map.total_pages(FREE), 7);
+        assert_eq!(map.total_pages(EfiMemoryType::EfiACPIReclaimMemory as UINT32), 0);
+        assert_eq!(map.page_totals(), [(LOADER, 1), (FREE, 7)]);
+    }
+}
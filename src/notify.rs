@@ -0,0 +1,101 @@
+use alloc::rc::Rc;
+
+use core::cell::Cell;
+use core::marker::PhantomData;
+
+use crate::event::Event;
+use crate::proto::Protocol;
+use crate::types::*;
+use crate::*;
+
+/// Notification of new installations of a protocol.
+///
+/// Only handles that gain the protocol after registration are reported.
+/// Closing the underlying event on drop also cancels the registration.
+pub struct ProtocolNotify<P: Protocol> {
+    event: Event,
+    registration: Rc<Cell<*mut VOID>>,
+    _protocol: PhantomData<P>,
+}
+
+impl<P: Protocol> ProtocolNotify<P> {
+    /// Registers for `P`; new handles are collected with
+    /// [`ProtocolNotify::next_handle`] or [`ProtocolNotify::handles`].
+    pub fn new() -> Result<Self, EfiStatus> {
+        let event = Event::new(0)?;
+        // SAFETY: the registration is dropped together with the event
+        let registration = unsafe {
+            BOOT_SERVICES.register_protocol_notify(&P::GUID, event.as_raw())?
+        };
+        Ok(ProtocolNotify {
+            event,
+            registration: Rc::new(Cell::new(registration)),
+            _protocol: PhantomData,
+        })
+    }
+
+    /// Registers for `P` and calls `callback` at `notify_tpl` with every
+    /// handle that gains it.
+    pub fn with_callback<F>(notify_tpl: EfiTpl, mut callback: F) -> Result<Self, EfiStatus>
+    where
+        F: FnMut(EfiHandle) + 'static,
+    {
+        let registration: Rc<Cell<*mut VOID>> = Rc::new(Cell::new(core::ptr::null_mut()));
+        let key = registration.clone();
+        let event = Event::with_notify(EVT_NOTIFY_SIGNAL, notify_tpl, move |_| {
+            if key.get().is_null() {
+                return;
+            }
+            // SAFETY: the event delivering this notification is still open
+            while let Some(handle) = unsafe { next_registered_handle::<P>(key.get()) } {
+                callback(handle);
+            }
+        })?;
+        // SAFETY: the registration is dropped together with the event
+        registration.set(unsafe {
+            BOOT_SERVICES.register_protocol_notify(&P::GUID, event.as_raw())?
+        });
+        Ok(ProtocolNotify { event, registration, _protocol: PhantomData })
+    }
+
+    /// The event signaled on installation, e.g. for [`Event::wait_any`].
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+
+    /// Returns the next newly installed handle, if any.
+    pub fn next_handle(&self) -> Option<EfiHandle> {
+        // SAFETY: `self.event` keeps the registration alive
+        unsafe { next_registered_handle::<P>(self.registration.get()) }
+    }
+
+    /// Drains the handles installed since the last call.
+    pub fn handles(&self) -> impl Iterator<Item = EfiHandle> + '_ {
+        core::iter::from_fn(move || self.next_handle())
+    }
+
+    /// Blocks until the protocol is installed somewhere and returns the
+    /// new handle.
+    pub fn wait(&self) -> Result<EfiHandle, EfiStatus> {
+        loop {
+            if let Some(handle) = self.next_handle() {
+                return Ok(handle);
+            }
+            self.event.wait()?;
+        }
+    }
+}
+
+// ByRegisterNotify hands out one new handle per call. `registration` must
+// come from a register_protocol_notify whose event is still open.
+unsafe fn next_registered_handle<P: Protocol>(registration: *mut VOID) -> Option<EfiHandle> {
+    let mut handle: [EfiHandle; 1] = [core::ptr::null_mut()];
+    unsafe {
+        BOOT_SERVICES.locate_handle(
+            EfiLocateSearchType::ByRegisterNotify,
+            Some(&P::GUID),
+            registration,
+            &mut handle,
+        )
+    }.ok().filter(|&count| count > 0).map(|_| handle[0])
+}
@@ -0,0 +1,59 @@
+use core::ffi::CStr;
+
+use crate::proto::Protocol;
+use crate::safeptr::*;
+use crate::types::*;
+
+pub const EFI_COMPONENT_NAME2_PROTOCOL_GUID: EfiGuid = EfiGuid {
+    data1: 0x6a7a5cff,
+    data2: 0xe8d9,
+    data3: 0x4f70,
+    data4: [0xba,0xda,0x75,0xab,0x30,0x25,0xce,0x14],
+};
+
+#[repr(C)]
+pub struct EfiComponentName2Protocol {
+    pub get_driver_name: unsafe extern "efiapi" fn(
+        this: *mut EfiComponentName2Protocol,
+        language: *mut CHAR8,
+        driver_name: *mut *mut CHAR16,
+    ) -> EfiStatus,
+
+    pub get_controller_name: unsafe extern "efiapi" fn(
+        this: *mut EfiComponentName2Protocol,
+        controller_handle: EfiHandle,
+        child_handle: EfiHandle,
+        language: *mut CHAR8,
+        controller_name: *mut *mut CHAR16,
+    ) -> EfiStatus,
+
+    /// Semicolon-separated RFC 4646 language codes, e.g. `en;fr`.
+    pub supported_languages: *mut CHAR8,
+}
+
+pub struct ComponentName2Protocol {
+    protocol: ThreadSafePtr<EfiComponentName2Protocol>,
+}
+
+impl Protocol for ComponentName2Protocol {
+    const GUID: EfiGuid = EFI_COMPONENT_NAME2_PROTOCOL_GUID;
+
+    unsafe fn from_raw(interface: *mut VOID) -> Self {
+        unsafe { ComponentName2Protocol::new(interface as *mut _) }
+    }
+}
+
+impl ComponentName2Protocol {
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid protocol instance.
+    pub unsafe fn new(ptr: *mut EfiComponentName2Protocol) -> Self {
+        ComponentName2Protocol {
+            protocol: unsafe { ThreadSafePtr::new(ptr) },
+        }
+    }
+
+    pub fn supported_languages(&self) -> &CStr {
+        unsafe { CStr::from_ptr((*self.protocol.as_ptr()).supported_languages as *const _) }
+    }
+}
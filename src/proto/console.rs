@@ -1,5 +1,6 @@
 use crate::safeptr::*;
 use crate::types::*;
+use crate::proto::Protocol;
 use crate::*;
 
 #[cfg(feature = "global-alloc")]
@@ -16,24 +17,35 @@ pub const EFI_SIMPLE_TEXT_INPUT_PROTOCOL_GUID: EfiGuid = EfiGuid {
 
 #[repr(C)]
 pub struct EfiSimpleTextInputProtocol {
-    reset: unsafe extern "efiapi" fn(
+    pub reset: unsafe extern "efiapi" fn(
         this: *mut EfiSimpleTextInputProtocol,
         extended_verification: BOOLEAN,
     ) -> EfiStatus,
 
-    read_key_stroke: unsafe extern "efiapi" fn(
+    pub read_key_stroke: unsafe extern "efiapi" fn(
         this: *mut EfiSimpleTextInputProtocol,
         key: *mut EfiInputKey,
     ) -> EfiStatus,
 
-    wait_for_key: EfiEvent,
+    pub wait_for_key: EfiEvent,
 }
 
 pub struct SimpleTextInputProtocol {
     protocol: ThreadSafePtr<EfiSimpleTextInputProtocol>,
 }
 
+impl Protocol for SimpleTextInputProtocol {
+    const GUID: EfiGuid = EFI_SIMPLE_TEXT_INPUT_PROTOCOL_GUID;
+
+    unsafe fn from_raw(interface: *mut VOID) -> Self {
+        unsafe { SimpleTextInputProtocol::new(interface as *mut _) }
+    }
+}
+
 impl SimpleTextInputProtocol {
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid protocol instance.
     pub unsafe fn new(ptr: *mut EfiSimpleTextInputProtocol) -> Self {
         SimpleTextInputProtocol {
             protocol: unsafe { ThreadSafePtr::new(ptr) },
@@ -83,56 +95,67 @@ pub struct EfiSimpleTextOutputProtocol {
         extended_verification: BOOLEAN,
     ) -> EfiStatus,
 
-    output_string: unsafe extern "efiapi" fn(
+    pub output_string: unsafe extern "efiapi" fn(
         this: *mut EfiSimpleTextOutputProtocol,
         string: *mut CHAR16,
     ) -> EfiStatus,
 
-    test_string: unsafe extern "efiapi" fn(
+    pub test_string: unsafe extern "efiapi" fn(
         this: *mut EfiSimpleTextOutputProtocol,
         string: *mut CHAR16,
     ) -> EfiStatus,
 
-    query_mode: unsafe extern "efiapi" fn(
+    pub query_mode: unsafe extern "efiapi" fn(
         this: *mut EfiSimpleTextOutputProtocol,
         mode_number: UINTN,
         columns: *mut UINTN,
         rows: *mut UINTN,
     ) -> EfiStatus,
 
-    set_mode: unsafe extern "efiapi" fn(
+    pub set_mode: unsafe extern "efiapi" fn(
         this: *mut EfiSimpleTextOutputProtocol,
         mode_number: UINTN,
     ) -> EfiStatus,
 
-    set_attribute: unsafe extern "efiapi" fn(
+    pub set_attribute: unsafe extern "efiapi" fn(
         this: *mut EfiSimpleTextOutputProtocol,
         attribute: UINTN,
     ) -> EfiStatus,
 
-    clear_screen: unsafe extern "efiapi" fn(
+    pub clear_screen: unsafe extern "efiapi" fn(
         this: *mut EfiSimpleTextOutputProtocol,
     ) -> EfiStatus,
 
-    set_cursor_position: unsafe extern "efiapi" fn(
+    pub set_cursor_position: unsafe extern "efiapi" fn(
         this: *mut EfiSimpleTextOutputProtocol,
         column: UINTN,
         row: UINTN,
     ) -> EfiStatus,
 
-    enable_cursor: unsafe extern "efiapi" fn(
+    pub enable_cursor: unsafe extern "efiapi" fn(
         this: *mut EfiSimpleTextOutputProtocol,
         visible: BOOLEAN,
     ) -> EfiStatus,
 
-
+    pub mode: *mut SimpleTextOutputMode,
 }
 
 pub struct SimpleTextOutputProtocol {
     protocol: ThreadSafePtr<EfiSimpleTextOutputProtocol>,
 }
 
+impl Protocol for SimpleTextOutputProtocol {
+    const GUID: EfiGuid = EFI_SIMPLE_TEXT_OUTPUT_PROTOCOL_GUID;
+
+    unsafe fn from_raw(interface: *mut VOID) -> Self {
+        unsafe { SimpleTextOutputProtocol::new(interface as *mut _) }
+    }
+}
+
 impl SimpleTextOutputProtocol {
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid protocol instance.
     pub unsafe fn new(ptr: *mut EfiSimpleTextOutputProtocol) -> Self {
         SimpleTextOutputProtocol {
             protocol: unsafe { ThreadSafePtr::new(ptr) },
@@ -165,7 +188,7 @@ impl SimpleTextOutputProtocol {
             let mut idx = 0;
             for c in string.encode_utf16() {
                 if idx > STACK_BUF_SIZE - 1 {
-                    return Err(EfiStatus::EfiInvalidParameter);
+                    return Err(EfiStatus::INVALID_PARAMETER);
                 }
                 stack_buf[idx] = c;
                 idx += 1;
@@ -201,7 +224,7 @@ impl SimpleTextOutputProtocol {
             let mut idx = 0;
             for c in string.encode_utf16() {
                 if idx > STACK_BUF_SIZE - 1 {
-                    return Err(EfiStatus::EfiInvalidParameter);
+                    return Err(EfiStatus::INVALID_PARAMETER);
                 }
                 stack_buf[idx] = c;
                 idx += 1;
@@ -0,0 +1,211 @@
+use alloc::vec::Vec;
+
+use crate::proto::Protocol;
+use crate::safeptr::*;
+use crate::types::*;
+
+pub const EFI_DEVICE_PATH_PROTOCOL_GUID: EfiGuid = EfiGuid {
+    data1: 0x09576e91,
+    data2: 0x6d3f,
+    data3: 0x11d2,
+    data4: [0x8e,0x39,0x00,0xa0,0xc9,0x69,0x72,0x3b],
+};
+
+pub const HARDWARE_DEVICE_PATH: UINT8   = 0x01;
+pub const ACPI_DEVICE_PATH: UINT8       = 0x02;
+pub const MESSAGING_DEVICE_PATH: UINT8  = 0x03;
+pub const MEDIA_DEVICE_PATH: UINT8      = 0x04;
+pub const BBS_DEVICE_PATH: UINT8        = 0x05;
+pub const END_DEVICE_PATH_TYPE: UINT8   = 0x7f;
+
+pub const MEDIA_FILEPATH_DP: UINT8                  = 0x04;
+pub const END_INSTANCE_DEVICE_PATH_SUBTYPE: UINT8   = 0x01;
+pub const END_ENTIRE_DEVICE_PATH_SUBTYPE: UINT8     = 0xff;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct EfiDevicePathProtocol {
+    pub path_type: UINT8,
+    pub sub_type: UINT8,
+    pub length: [UINT8; 2],
+}
+
+impl EfiDevicePathProtocol {
+    pub fn node_length(&self) -> usize {
+        u16::from_le_bytes(self.length) as usize
+    }
+
+    pub fn is_end(&self) -> bool {
+        self.path_type == END_DEVICE_PATH_TYPE
+            && self.sub_type == END_ENTIRE_DEVICE_PATH_SUBTYPE
+    }
+}
+
+const NODE_HEADER_SIZE: usize = core::mem::size_of::<EfiDevicePathProtocol>();
+
+/// Device path installed on a handle.
+pub struct DevicePathProtocol {
+    protocol: ThreadSafePtr<EfiDevicePathProtocol>,
+}
+
+impl Protocol for DevicePathProtocol {
+    const GUID: EfiGuid = EFI_DEVICE_PATH_PROTOCOL_GUID;
+
+    unsafe fn from_raw(interface: *mut VOID) -> Self {
+        unsafe { DevicePathProtocol::new(interface as *mut _) }
+    }
+}
+
+impl DevicePathProtocol {
+    /// # Safety
+    ///
+    /// `ptr` must point to a device path terminated by an end node.
+    pub unsafe fn new(ptr: *mut EfiDevicePathProtocol) -> Self {
+        DevicePathProtocol {
+            protocol: unsafe { ThreadSafePtr::new(ptr) },
+        }
+    }
+
+    pub fn as_ptr(&self) -> *mut EfiDevicePathProtocol {
+        self.protocol.as_ptr()
+    }
+
+    /// The whole path, including the end node.
+    pub fn as_bytes(&self) -> &[u8] {
+        let base = self.protocol.as_ptr() as *const u8;
+        let mut size = 0;
+        // SAFETY: the path is terminated, and every node reports its length
+        unsafe {
+            loop {
+                let node = &*(base.add(size) as *const EfiDevicePathProtocol);
+                let len = node.node_length();
+                if len < NODE_HEADER_SIZE {
+                    break;
+                }
+                size += len;
+                if node.is_end() {
+                    break;
+                }
+            }
+            core::slice::from_raw_parts(base, size)
+        }
+    }
+}
+
+/// Owned, end-terminated device path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DevicePathBuf {
+    bytes: Vec<u8>,
+}
+
+impl DevicePathBuf {
+    const END_NODE: [u8; 4] = [END_DEVICE_PATH_TYPE, END_ENTIRE_DEVICE_PATH_SUBTYPE, 4, 0];
+
+    /// Validates `bytes` as a sequence of nodes ending in a 4-byte end node.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut offset = 0;
+        loop {
+            let header = bytes.get(offset..offset + NODE_HEADER_SIZE)?;
+            let len = u16::from_le_bytes([header[2], header[3]]) as usize;
+            if len < NODE_HEADER_SIZE || offset + len > bytes.len() {
+                return None;
+            }
+            offset += len;
+            if header[0] == END_DEVICE_PATH_TYPE && header[1] == END_ENTIRE_DEVICE_PATH_SUBTYPE {
+                // push_file_path replaces exactly `END_NODE`
+                if len != Self::END_NODE.len() {
+                    return None;
+                }
+                break;
+            }
+        }
+        Some(DevicePathBuf { bytes: bytes[..offset].to_vec() })
+    }
+
+    /// A path holding a single media file path node, e.g. `\EFI\BOOT\BOOTX64.EFI`.
+    pub fn file_path(path: &str) -> Result<Self, EfiStatus> {
+        let mut buf = DevicePathBuf { bytes: Self::END_NODE.to_vec() };
+        buf.push_file_path(path)?;
+        Ok(buf)
+    }
+
+    /// Appends a media file path node before the end node. Fails with
+    /// `EfiStatus::INVALID_PARAMETER` if the node would exceed the 64 KiB a
+    /// node length can describe.
+    pub fn push_file_path(&mut self, path: &str) -> Result<(), EfiStatus> {
+        let name: Vec<u16> = path.encode_utf16().chain(core::iter::once(0)).collect();
+        let len = u16::try_from(NODE_HEADER_SIZE + name.len() * 2)
+            .map_err(|_| EfiStatus::INVALID_PARAMETER)?;
+
+        let end = self.bytes.len() - Self::END_NODE.len();
+        self.bytes.truncate(end);
+        self.bytes.extend_from_slice(&[MEDIA_DEVICE_PATH, MEDIA_FILEPATH_DP]);
+        self.bytes.extend_from_slice(&len.to_le_bytes());
+        for c in name {
+            self.bytes.extend_from_slice(&c.to_le_bytes());
+        }
+        self.bytes.extend_from_slice(&Self::END_NODE);
+        Ok(())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Pointer for firmware calls; firmware only reads through it.
+    pub fn as_ptr(&self) -> *mut EfiDevicePathProtocol {
+        self.bytes.as_ptr() as *mut EfiDevicePathProtocol
+    }
+}
+
+impl From<&DevicePathProtocol> for DevicePathBuf {
+    /// Copies the nodes of `path` and terminates them with a 4-byte end
+    /// node, whatever length firmware gave its own.
+    fn from(path: &DevicePathProtocol) -> Self {
+        let bytes = path.as_bytes();
+        let mut end = 0;
+        // every node in `as_bytes` has a valid length
+        while end < bytes.len() {
+            let node = &bytes[end..end + NODE_HEADER_SIZE];
+            if node[0] == END_DEVICE_PATH_TYPE && node[1] == END_ENTIRE_DEVICE_PATH_SUBTYPE {
+                break;
+            }
+            end += u16::from_le_bytes([node[2], node[3]]) as usize;
+        }
+        let mut bytes = bytes[..end].to_vec();
+        bytes.extend_from_slice(&Self::END_NODE);
+        DevicePathBuf { bytes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a vendor-defined hardware node followed by a padded end node
+    const PADDED: [u8; 14] = [HARDWARE_DEVICE_PATH, 4, 6, 0, 0xaa, 0xbb,
+                              END_DEVICE_PATH_TYPE, END_ENTIRE_DEVICE_PATH_SUBTYPE, 8, 0,
+                              0, 0, 0, 0];
+
+    #[test]
+    fn from_bytes_rejects_padded_end_node() {
+        assert!(DevicePathBuf::from_bytes(&PADDED).is_none());
+        let mut exact = PADDED[..10].to_vec();
+        exact[8] = 4;
+        assert_eq!(DevicePathBuf::from_bytes(&exact).unwrap().as_bytes(), &exact[..]);
+    }
+
+    #[test]
+    fn push_file_path_after_padded_end_node() {
+        let mut firmware = PADDED;
+        let path = unsafe { DevicePathProtocol::new(firmware.as_mut_ptr() as *mut _) };
+        let mut buf = DevicePathBuf::from(&path);
+        assert_eq!(buf.as_bytes().len(), 10);
+
+        buf.push_file_path("a").unwrap();
+        assert_eq!(buf.as_bytes(), &[HARDWARE_DEVICE_PATH, 4, 6, 0, 0xaa, 0xbb,
+                                     MEDIA_DEVICE_PATH, MEDIA_FILEPATH_DP, 8, 0, b'a', 0, 0, 0,
+                                     END_DEVICE_PATH_TYPE, END_ENTIRE_DEVICE_PATH_SUBTYPE, 4, 0]);
+        assert!(DevicePathBuf::from_bytes(buf.as_bytes()).is_some());
+    }
+}
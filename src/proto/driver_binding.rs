@@ -0,0 +1,73 @@
+use crate::proto::Protocol;
+use crate::proto::device_path::EfiDevicePathProtocol;
+use crate::safeptr::*;
+use crate::types::*;
+
+pub const EFI_DRIVER_BINDING_PROTOCOL_GUID: EfiGuid = EfiGuid {
+    data1: 0x18a031ab,
+    data2: 0xb443,
+    data3: 0x4d1a,
+    data4: [0xa5,0xc0,0x0c,0x09,0x26,0x1e,0x9f,0x71],
+};
+
+#[repr(C)]
+pub struct EfiDriverBindingProtocol {
+    pub supported: unsafe extern "efiapi" fn(
+        this: *mut EfiDriverBindingProtocol,
+        controller_handle: EfiHandle,
+        remaining_device_path: *mut EfiDevicePathProtocol,
+    ) -> EfiStatus,
+
+    pub start: unsafe extern "efiapi" fn(
+        this: *mut EfiDriverBindingProtocol,
+        controller_handle: EfiHandle,
+        remaining_device_path: *mut EfiDevicePathProtocol,
+    ) -> EfiStatus,
+
+    pub stop: unsafe extern "efiapi" fn(
+        this: *mut EfiDriverBindingProtocol,
+        controller_handle: EfiHandle,
+        number_of_children: UINTN,
+        child_handle_buffer: *mut EfiHandle,
+    ) -> EfiStatus,
+
+    pub version: UINT32,
+    pub image_handle: EfiHandle,
+    pub driver_binding_handle: EfiHandle,
+}
+
+pub struct DriverBindingProtocol {
+    protocol: ThreadSafePtr<EfiDriverBindingProtocol>,
+}
+
+impl Protocol for DriverBindingProtocol {
+    const GUID: EfiGuid = EFI_DRIVER_BINDING_PROTOCOL_GUID;
+
+    unsafe fn from_raw(interface: *mut VOID) -> Self {
+        unsafe { DriverBindingProtocol::new(interface as *mut _) }
+    }
+}
+
+impl DriverBindingProtocol {
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid protocol instance.
+    pub unsafe fn new(ptr: *mut EfiDriverBindingProtocol) -> Self {
+        DriverBindingProtocol {
+            protocol: unsafe { ThreadSafePtr::new(ptr) },
+        }
+    }
+
+    pub fn version(&self) -> UINT32 {
+        unsafe { (*self.protocol.as_ptr()).version }
+    }
+
+    /// Image that produced the driver.
+    pub fn image_handle(&self) -> EfiHandle {
+        unsafe { (*self.protocol.as_ptr()).image_handle }
+    }
+
+    pub fn driver_binding_handle(&self) -> EfiHandle {
+        unsafe { (*self.protocol.as_ptr()).driver_binding_handle }
+    }
+}
@@ -0,0 +1,250 @@
+//! Publishing protocols implemented in Rust.
+//!
+//! A [`ProtocolInstance`] places the `#[repr(C)]` function table first and
+//! the Rust state after it, so the `this` pointer firmware callers pass to
+//! each function can be turned back into the whole instance. Calls can nest,
+//! so the instance is only shared and state that changes goes behind a
+//! [`tpl::Mutex`] or `Cell`:
+//!
+//! ```ignore
+//! unsafe extern "efiapi" fn clear_screen(
+//!     this: *mut EfiSimpleTextOutputProtocol,
+//! ) -> EfiStatus {
+//!     let console = unsafe { ProtocolInstance::<_, Mutex<MyConsole>>::from_this(this) };
+//!     console.data().lock().clear();
+//!     EfiStatus::SUCCESS
+//! }
+//! ```
+
+use alloc::boxed::Box;
+
+use crate::proto::component_name::*;
+use crate::proto::console::*;
+use crate::proto::driver_binding::*;
+use crate::proto::loaded_image::*;
+use crate::proto::Protocol;
+use crate::types::*;
+use crate::*;
+
+/// The `#[repr(C)]` interface structure behind a [`Protocol`] wrapper.
+///
+/// # Safety
+///
+/// `Self` must have exactly the layout of the interface identified by
+/// the GUID of `Self::Protocol`, so that other images can call through it.
+/// Variable-length interfaces such as device paths don't qualify: the Rust
+/// state after `Self` would be read as part of the interface.
+pub unsafe trait ProtocolInterface: 'static {
+    type Protocol: Protocol;
+}
+
+unsafe impl ProtocolInterface for EfiSimpleTextInputProtocol {
+    type Protocol = SimpleTextInputProtocol;
+}
+
+unsafe impl ProtocolInterface for EfiSimpleTextOutputProtocol {
+    type Protocol = SimpleTextOutputProtocol;
+}
+
+unsafe impl ProtocolInterface for EfiLoadedImageProtocol {
+    type Protocol = LoadedImageProtocol;
+}
+
+unsafe impl ProtocolInterface for EfiDriverBindingProtocol {
+    type Protocol = DriverBindingProtocol;
+}
+
+unsafe impl ProtocolInterface for EfiComponentName2Protocol {
+    type Protocol = ComponentName2Protocol;
+}
+
+fn guid<P: ProtocolInterface>() -> &'static EfiGuid {
+    &<P::Protocol as Protocol>::GUID
+}
+
+/// Protocol interface `P` followed by the Rust state `T` backing it.
+#[repr(C)]
+pub struct ProtocolInstance<P: ProtocolInterface, T> {
+    interface: P,
+    data: T,
+}
+
+impl<P: ProtocolInterface, T> ProtocolInstance<P, T> {
+    pub fn new(interface: P, data: T) -> Box<Self> {
+        Box::new(ProtocolInstance { interface, data })
+    }
+
+    /// Recovers the instance from the `this` pointer handed to one of the
+    /// interface functions.
+    ///
+    /// # Safety
+    ///
+    /// `this` must be the interface of a live `ProtocolInstance<P, T>`, and
+    /// the returned reference must not outlive the call it was made in.
+    pub unsafe fn from_this<'a>(this: *mut P) -> &'a Self {
+        // SAFETY: repr(C) puts `interface` at offset zero
+        unsafe { &*(this as *const Self) }
+    }
+
+    pub fn interface(&self) -> &P {
+        &self.interface
+    }
+
+    pub fn interface_ptr(&mut self) -> *mut P {
+        &mut self.interface
+    }
+
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+}
+
+/// A Rust-backed protocol installed on a handle.
+///
+/// Dropping it uninstalls the protocol; if firmware refuses because the
+/// interface is still in use, the instance is leaked rather than freed.
+pub struct InstalledProtocol<P: ProtocolInterface, T> {
+    handle: EfiHandle,
+    instance: Option<Box<ProtocolInstance<P, T>>>,
+}
+
+impl<P: ProtocolInterface, T> InstalledProtocol<P, T> {
+    pub fn handle(&self) -> EfiHandle {
+        self.handle
+    }
+
+    fn interface_ptr(&mut self) -> *mut VOID {
+        self.instance.as_mut().unwrap().interface_ptr() as *mut VOID
+    }
+
+    /// Replaces the installed instance, returning the previous one.
+    /// Drivers that opened the protocol are stopped and restarted.
+    pub fn reinstall(&mut self,
+                     mut instance: Box<ProtocolInstance<P, T>>
+    ) -> Result<Box<ProtocolInstance<P, T>>, EfiStatus> {
+        let old = self.interface_ptr();
+        unsafe {
+            BOOT_SERVICES.reinstall_protocol_interface(
+                self.handle,
+                guid::<P>(),
+                old,
+                instance.interface_ptr() as *mut VOID,
+            )?;
+        }
+        Ok(self.instance.replace(instance).unwrap())
+    }
+
+    /// Uninstalls the protocol and hands back the instance, or returns
+    /// `self` unchanged if firmware refuses.
+    pub fn uninstall(mut self) -> Result<Box<ProtocolInstance<P, T>>, (Self, EfiStatus)> {
+        let interface = self.interface_ptr();
+        let result = unsafe {
+            BOOT_SERVICES.uninstall_protocol_interface(self.handle, guid::<P>(), interface)
+        };
+        match result {
+            Ok(()) => Ok(self.instance.take().unwrap()),
+            Err(status) => Err((self, status)),
+        }
+    }
+}
+
+impl<P: ProtocolInterface, T> Drop for InstalledProtocol<P, T> {
+    fn drop(&mut self) {
+        let Some(mut instance) = self.instance.take() else {
+            return;
+        };
+        let uninstalled = BOOT_SERVICES.get().is_some_and(|bs| unsafe {
+            bs.uninstall_protocol_interface(
+                self.handle,
+                guid::<P>(),
+                instance.interface_ptr() as *mut VOID,
+            ).is_ok()
+        });
+        if !uninstalled {
+            Box::leak(instance);
+        }
+    }
+}
+
+// the multiple-interface services are variadic, terminated by a null GUID
+macro_rules! call_multiple {
+    ($f:expr, $handle:expr, $list:expr) => {{
+        let list = $list;
+        let g = |i: usize| list[i].0 as *const EfiGuid;
+        let p = |i: usize| list[i].1;
+        let end = core::ptr::null::<EfiGuid>();
+        match list.len() {
+            1 => $f($handle, g(0), p(0), end),
+            2 => $f($handle, g(0), p(0), g(1), p(1), end),
+            3 => $f($handle, g(0), p(0), g(1), p(1), g(2), p(2), end),
+            4 => $f($handle, g(0), p(0), g(1), p(1), g(2), p(2), g(3), p(3), end),
+            5 => $f($handle, g(0), p(0), g(1), p(1), g(2), p(2), g(3), p(3),
+                    g(4), p(4), end),
+            6 => $f($handle, g(0), p(0), g(1), p(1), g(2), p(2), g(3), p(3),
+                    g(4), p(4), g(5), p(5), end),
+            7 => $f($handle, g(0), p(0), g(1), p(1), g(2), p(2), g(3), p(3),
+                    g(4), p(4), g(5), p(5), g(6), p(6), end),
+            8 => $f($handle, g(0), p(0), g(1), p(1), g(2), p(2), g(3), p(3),
+                    g(4), p(4), g(5), p(5), g(6), p(6), g(7), p(7), end),
+            _ => EfiStatus::INVALID_PARAMETER,
+        }
+    }};
+}
+
+impl BootServices {
+    /// Installs `instance` on `handle`, or on a new handle if `None`.
+    ///
+    /// # Safety
+    ///
+    /// Every function in the interface must implement the protocol for any
+    /// caller, for as long as it stays installed. `T` is `'static` because
+    /// an instance that can't be uninstalled is leaked, not freed.
+    pub unsafe fn install_protocol<P: ProtocolInterface, T: 'static>(&self,
+                                                                     handle: Option<EfiHandle>,
+                                                                     mut instance: Box<ProtocolInstance<P, T>>
+    ) -> Result<InstalledProtocol<P, T>, EfiStatus> {
+        let handle = unsafe {
+            self.install_protocol_interface(
+                handle,
+                guid::<P>(),
+                instance.interface_ptr() as *mut VOID,
+            )?
+        };
+        Ok(InstalledProtocol { handle, instance: Some(instance) })
+    }
+
+    /// Installs several interfaces on `handle`, or on a new handle if
+    /// `None`, all or nothing. At most eight interfaces are supported.
+    ///
+    /// # Safety
+    ///
+    /// Each interface must match its GUID and outlive its installation.
+    pub unsafe fn install_multiple_protocol_interfaces(&self,
+                                                       handle: Option<EfiHandle>,
+                                                       interfaces: &[(&EfiGuid, *mut VOID)]
+    ) -> Result<EfiHandle, EfiStatus> {
+        let mut handle = handle.unwrap_or(core::ptr::null_mut());
+        let f = unsafe { (*self.services.as_ptr()).install_multiple_protocol_interfaces };
+        let status = unsafe { call_multiple!(f, &mut handle, interfaces) };
+        efi_try!(status).map(|_| handle)
+    }
+
+    /// Removes several interfaces from `handle`, all or nothing. At most
+    /// eight interfaces are supported.
+    ///
+    /// # Safety
+    ///
+    /// No other image may still be using the interfaces.
+    pub unsafe fn uninstall_multiple_protocol_interfaces(&self,
+                                                         handle: EfiHandle,
+                                                         interfaces: &[(&EfiGuid, *mut VOID)]
+    ) -> Result<(), EfiStatus> {
+        let f = unsafe { (*self.services.as_ptr()).uninstall_multiple_protocol_interfaces };
+        let status = unsafe { call_multiple!(f, handle, interfaces) };
+        efi_try!(status)
+    }
+}
@@ -0,0 +1,113 @@
+use crate::proto::Protocol;
+use crate::proto::device_path::EfiDevicePathProtocol;
+use crate::safeptr::*;
+use crate::tables::st::EfiSystemTable;
+use crate::types::*;
+
+pub const EFI_LOADED_IMAGE_PROTOCOL_GUID: EfiGuid = EfiGuid {
+    data1: 0x5b1b31a1,
+    data2: 0x9562,
+    data3: 0x11d2,
+    data4: [0x8e,0x3f,0x00,0xa0,0xc9,0x69,0x72,0x3b],
+};
+
+pub const EFI_LOADED_IMAGE_PROTOCOL_REVISION: UINT32 = 0x1000;
+
+#[repr(C)]
+pub struct EfiLoadedImageProtocol {
+    pub revision: UINT32,
+    pub parent_handle: EfiHandle,
+    pub system_table: *mut EfiSystemTable,
+
+    // source location of the image
+
+    pub device_handle: EfiHandle,
+    pub file_path: *mut EfiDevicePathProtocol,
+    pub reserved: *mut VOID,
+
+    // image's load options
+
+    pub load_options_size: UINT32,
+    pub load_options: *mut VOID,
+
+    // location where image was loaded
+
+    pub image_base: *mut VOID,
+    pub image_size: UINT64,
+    pub image_code_type: UINT32,
+    pub image_data_type: UINT32,
+    pub unload: Option<unsafe extern "efiapi" fn(
+        image_handle: EfiHandle,
+    ) -> EfiStatus>,
+}
+
+pub struct LoadedImageProtocol {
+    protocol: ThreadSafePtr<EfiLoadedImageProtocol>,
+}
+
+impl Protocol for LoadedImageProtocol {
+    const GUID: EfiGuid = EFI_LOADED_IMAGE_PROTOCOL_GUID;
+
+    unsafe fn from_raw(interface: *mut VOID) -> Self {
+        unsafe { LoadedImageProtocol::new(interface as *mut _) }
+    }
+}
+
+impl LoadedImageProtocol {
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid protocol instance.
+    pub unsafe fn new(ptr: *mut EfiLoadedImageProtocol) -> Self {
+        LoadedImageProtocol {
+            protocol: unsafe { ThreadSafePtr::new(ptr) },
+        }
+    }
+
+    pub fn parent_handle(&self) -> EfiHandle {
+        unsafe { (*self.protocol.as_ptr()).parent_handle }
+    }
+
+    /// Handle of the device the image was loaded from.
+    pub fn device_handle(&self) -> EfiHandle {
+        unsafe { (*self.protocol.as_ptr()).device_handle }
+    }
+
+    /// File path of the image relative to `device_handle`.
+    pub fn file_path(&self) -> *mut EfiDevicePathProtocol {
+        unsafe { (*self.protocol.as_ptr()).file_path }
+    }
+
+    pub fn image_base(&self) -> *mut VOID {
+        unsafe { (*self.protocol.as_ptr()).image_base }
+    }
+
+    pub fn image_size(&self) -> UINT64 {
+        unsafe { (*self.protocol.as_ptr()).image_size }
+    }
+
+    /// Raw load options, usually a UTF-16 command line.
+    pub fn load_options(&self) -> &[u8] {
+        unsafe {
+            let image = &*self.protocol.as_ptr();
+            if image.load_options.is_null() {
+                return &[];
+            }
+            core::slice::from_raw_parts(
+                image.load_options as *const u8,
+                image.load_options_size as usize,
+            )
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `options` must stay alive until the image has finished using it,
+    /// typically until `start_image` returns.
+    pub unsafe fn set_load_options(&self, options: &[u8]) {
+        unsafe {
+            let image = &mut *self.protocol.as_ptr();
+            image.load_options = options.as_ptr() as *mut VOID;
+            image.load_options_size = options.len() as UINT32;
+        }
+    }
+}
@@ -1,3 +1,201 @@
 pub mod console;
+pub mod device_path;
+pub mod loaded_image;
+pub mod instance;
+pub mod driver_binding;
+pub mod component_name;
 
 pub use console::{EfiSimpleTextInputProtocol, EfiSimpleTextOutputProtocol};
+pub use device_path::EfiDevicePathProtocol;
+pub use loaded_image::EfiLoadedImageProtocol;
+
+use crate::types::*;
+use crate::*;
+
+/// A protocol interface identified by its GUID.
+///
+/// Implemented by the safe wrapper of each protocol, so that boot services
+/// can locate and open it by type.
+pub trait Protocol: Sized {
+    const GUID: EfiGuid;
+
+    /// # Safety
+    ///
+    /// `interface` must point to a live instance of the protocol identified
+    /// by `Self::GUID`.
+    unsafe fn from_raw(interface: *mut VOID) -> Self;
+}
+
+/// How `BootServices::open_protocol` opens an interface.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OpenProtocolAttributes {
+    ByHandleProtocol,
+    GetProtocol,
+    Exclusive,
+    /// Used by a driver's `start` on the controller it manages; the agent
+    /// is the driver binding handle.
+    ByDriver { controller: EfiHandle },
+    /// Like `ByDriver`, disconnecting any other driver first.
+    ByDriverExclusive { controller: EfiHandle },
+    /// Records that `child` consumes the protocol of its parent controller.
+    ByChildController { child: EfiHandle },
+}
+
+impl OpenProtocolAttributes {
+    pub fn bits(&self) -> UINT32 {
+        match self {
+            OpenProtocolAttributes::ByHandleProtocol => EFI_OPEN_PROTOCOL_BY_HANDLE_PROTOCOL,
+            OpenProtocolAttributes::GetProtocol => EFI_OPEN_PROTOCOL_GET_PROTOCOL,
+            OpenProtocolAttributes::Exclusive => EFI_OPEN_PROTOCOL_EXCLUSIVE,
+            OpenProtocolAttributes::ByDriver { .. } => EFI_OPEN_PROTOCOL_BY_DRIVER,
+            OpenProtocolAttributes::ByDriverExclusive { .. } => {
+                EFI_OPEN_PROTOCOL_BY_DRIVER | EFI_OPEN_PROTOCOL_EXCLUSIVE
+            }
+            OpenProtocolAttributes::ByChildController { .. } => {
+                EFI_OPEN_PROTOCOL_BY_CHILD_CONTROLLER
+            }
+        }
+    }
+
+    /// Controller handle passed alongside the attributes.
+    pub fn controller(&self) -> EfiHandle {
+        match *self {
+            OpenProtocolAttributes::ByDriver { controller }
+            | OpenProtocolAttributes::ByDriverExclusive { controller } => controller,
+            OpenProtocolAttributes::ByChildController { child } => child,
+            _ => core::ptr::null_mut(),
+        }
+    }
+}
+
+/// An opened protocol interface, closed again on drop.
+pub struct ScopedProtocol<P: Protocol> {
+    interface: P,
+    handle: EfiHandle,
+    agent: EfiHandle,
+    controller: EfiHandle,
+}
+
+impl<P: Protocol> ScopedProtocol<P> {
+    /// Keeps the protocol open past this scope and returns the interface,
+    /// e.g. for a driver's `start`, whose `ByDriver` open has to last until
+    /// `stop`. Close it with [`BootServices::close_protocol`].
+    pub fn leak(self) -> P {
+        let this = core::mem::ManuallyDrop::new(self);
+        // SAFETY: `this` is never dropped, so the interface is moved out once
+        unsafe { core::ptr::read(&this.interface) }
+    }
+
+    pub fn handle(&self) -> EfiHandle {
+        self.handle
+    }
+}
+
+impl<P: Protocol> core::ops::Deref for ScopedProtocol<P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        &self.interface
+    }
+}
+
+impl<P: Protocol> core::ops::DerefMut for ScopedProtocol<P> {
+    fn deref_mut(&mut self) -> &mut P {
+        &mut self.interface
+    }
+}
+
+impl<P: Protocol> Drop for ScopedProtocol<P> {
+    fn drop(&mut self) {
+        if let Some(bs) = BOOT_SERVICES.get() {
+            // SAFETY: the handles were live when the protocol was opened,
+            // and the open keeps them from going away
+            let _ = unsafe {
+                bs.close_protocol_raw(self.handle, &P::GUID, self.agent, self.controller)
+            };
+        }
+    }
+}
+
+impl BootServices {
+    /// Opens `P` on `handle` on behalf of `agent`, usually the running image.
+    ///
+    /// # Safety
+    ///
+    /// `handle` and `agent` must be live handles, as must the controller in
+    /// `attributes`.
+    pub unsafe fn open_protocol<P: Protocol>(&self,
+                                             handle: EfiHandle,
+                                             agent: EfiHandle,
+                                             attributes: OpenProtocolAttributes
+    ) -> Result<ScopedProtocol<P>, EfiStatus> {
+        let controller = attributes.controller();
+        let interface = unsafe {
+            self.open_protocol_raw(handle, &P::GUID, agent, controller, attributes.bits())?
+        };
+        if interface.is_null() {
+            return Err(EfiStatus::UNSUPPORTED);
+        }
+        Ok(ScopedProtocol {
+            interface: unsafe { P::from_raw(interface) },
+            handle,
+            agent,
+            controller,
+        })
+    }
+
+    /// Closes an open of `P` kept with [`ScopedProtocol::leak`], passing the
+    /// `handle`, `agent` and controller it was opened with.
+    ///
+    /// # Safety
+    ///
+    /// The handles must be live, and the interface returned by `leak` must
+    /// not be used afterwards.
+    pub unsafe fn close_protocol<P: Protocol>(&self,
+                                              handle: EfiHandle,
+                                              agent: EfiHandle,
+                                              controller: EfiHandle
+    ) -> Result<(), EfiStatus> {
+        unsafe { self.close_protocol_raw(handle, &P::GUID, agent, controller) }
+    }
+
+    /// Returns whether `handle` supports `P`, without opening it.
+    ///
+    /// # Safety
+    ///
+    /// `handle` and `agent` must be live handles, and `controller` null or
+    /// live.
+    pub unsafe fn test_protocol<P: Protocol>(&self,
+                                             handle: EfiHandle,
+                                             agent: EfiHandle,
+                                             controller: EfiHandle
+    ) -> bool {
+        unsafe {
+            self.open_protocol_raw(handle,
+                                   &P::GUID,
+                                   agent,
+                                   controller,
+                                   EFI_OPEN_PROTOCOL_TEST_PROTOCOL)
+        }.is_ok()
+    }
+
+    /// Returns the first installed instance of `P`.
+    pub fn locate_protocol<P: Protocol>(&self) -> Result<P, EfiStatus> {
+        // SAFETY: no registration key
+        let interface = unsafe { self.locate_protocol_raw(&P::GUID, core::ptr::null_mut())? };
+        Ok(unsafe { P::from_raw(interface.as_ptr()) })
+    }
+
+    /// Legacy lookup of `P` on `handle`. Prefer
+    /// [`BootServices::open_protocol`], which tracks the opener.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a live handle.
+    pub unsafe fn handle_protocol<P: Protocol>(&self,
+                                               handle: EfiHandle
+    ) -> Result<P, EfiStatus> {
+        let interface = unsafe { self.handle_protocol_raw(handle, &P::GUID)? };
+        Ok(unsafe { P::from_raw(interface.as_ptr()) })
+    }
+}
@@ -0,0 +1,74 @@
+//! System reset through the runtime services.
+
+use alloc::vec::Vec;
+
+use crate::tables::rs::RuntimeServices;
+use crate::types::*;
+
+/// Data passed along with a reset: a reason string, followed by a GUID
+/// naming the reset for `EfiResetPlatformSpecific`.
+///
+/// The buffer is built up front, as the allocator is gone once boot
+/// services have exited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResetData {
+    bytes: Vec<u8>,
+}
+
+impl ResetData {
+    pub fn new(reason: &str) -> Self {
+        let mut bytes = Vec::with_capacity((reason.len() + 1) * 2);
+        for c in reason.encode_utf16().chain(core::iter::once(0)) {
+            bytes.extend_from_slice(&c.to_le_bytes());
+        }
+        ResetData { bytes }
+    }
+
+    /// Appends the platform-specific reset GUID after the reason.
+    pub fn with_platform_guid(reason: &str, guid: &EfiGuid) -> Self {
+        let mut data = ResetData::new(reason);
+        data.bytes.extend_from_slice(&guid.to_bytes());
+        data
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl RuntimeServices {
+    /// Resets the system. `status` is `EfiStatus::SUCCESS` for a normal
+    /// reset; otherwise `data` should describe the failure.
+    pub fn reset(&self,
+                 kind: EfiResetType,
+                 status: EfiStatus,
+                 data: Option<&ResetData>
+    ) -> ! {
+        let (size, ptr) = match data {
+            Some(data) => (data.bytes.len(), data.bytes.as_ptr() as *mut VOID),
+            None => (0, core::ptr::null_mut()),
+        };
+        unsafe {
+            ((*self.services.as_ptr()).reset_system)(kind, status, size, ptr);
+        }
+        // ResetSystem doesn't return
+        loop {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Powers the system off.
+    pub fn shutdown(&self) -> ! {
+        self.reset(EfiResetType::EfiResetShutdown, EfiStatus::SUCCESS, None)
+    }
+
+    /// Restarts the system without a full power cycle.
+    pub fn warm_reset(&self) -> ! {
+        self.reset(EfiResetType::EfiResetWarm, EfiStatus::SUCCESS, None)
+    }
+
+    /// Restarts the system, resetting all circuitry.
+    pub fn cold_reset(&self) -> ! {
+        self.reset(EfiResetType::EfiResetCold, EfiStatus::SUCCESS, None)
+    }
+}
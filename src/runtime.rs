@@ -0,0 +1,166 @@
+//! Support for code that keeps running after the switch to virtual mode.
+//!
+//! A runtime driver registers every pointer it holds into runtime memory with
+//! [`register_runtime_pointer`] and calls
+//! [`install_virtual_address_change_handler`] once. When the OS calls
+//! `SetVirtualAddressMap`, the handler converts the registered pointers,
+//! along with the `SYSTEM_TABLE` and `RUNTIME_SERVICES` globals.
+//!
+//! An OS loader instead calls [`RuntimeServices::set_virtual_address_map`]
+//! itself after `exit_boot_services`.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use spin::Mutex;
+
+use crate::event::Event;
+use crate::memory::MemoryMap;
+use crate::tables::rs::RuntimeServices;
+use crate::types::*;
+use crate::*;
+
+/// Capacity of the pointer registry. It's fixed because the handler runs
+/// after exit_boot_services, when nothing can be allocated.
+pub const MAX_RUNTIME_POINTERS: usize = 64;
+
+struct RuntimePointers {
+    // addresses of the registered `*mut T` slots
+    slots: [usize; MAX_RUNTIME_POINTERS],
+    len: usize,
+}
+
+static RUNTIME_POINTERS: Mutex<RuntimePointers> = Mutex::new(RuntimePointers {
+    slots: [0; MAX_RUNTIME_POINTERS],
+    len: 0,
+});
+
+static HANDLER_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Adds a pointer to be converted to its virtual address on
+/// `SetVirtualAddressMap`. Null pointers are left null. Fails with
+/// `EfiStatus::OUT_OF_RESOURCES` once [`MAX_RUNTIME_POINTERS`] are registered.
+///
+/// # Safety
+///
+/// `slot` must stay valid for writes until the address change, and the
+/// pointer in it must be null or point into runtime memory.
+pub unsafe fn register_runtime_pointer<T>(slot: *mut *mut T) -> Result<(), EfiStatus> {
+    let mut pointers = RUNTIME_POINTERS.lock();
+    if pointers.len == MAX_RUNTIME_POINTERS {
+        return Err(EfiStatus::OUT_OF_RESOURCES);
+    }
+    let len = pointers.len;
+    pointers.slots[len] = slot as usize;
+    pointers.len += 1;
+    Ok(())
+}
+
+/// Creates the `EVT_SIGNAL_VIRTUAL_ADDRESS_CHANGE` event that converts the
+/// registered pointers. The event lives for the rest of the image's life;
+/// later calls do nothing.
+pub fn install_virtual_address_change_handler() -> Result<(), EfiStatus> {
+    if HANDLER_INSTALLED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let event = unsafe {
+        Event::with_raw_notify(EVT_SIGNAL_VIRTUAL_ADDRESS_CHANGE,
+                               TPL_NOTIFY,
+                               virtual_address_change,
+                               core::ptr::null_mut())
+    };
+    match event {
+        Ok(event) => {
+            core::mem::forget(event);
+            Ok(())
+        }
+        Err(status) => {
+            HANDLER_INSTALLED.store(false, Ordering::SeqCst);
+            Err(status)
+        }
+    }
+}
+
+unsafe extern "efiapi" fn virtual_address_change(_event: EfiEvent, _context: *mut VOID) {
+    let Some(rs) = RUNTIME_SERVICES.get() else {
+        return;
+    };
+    // the table stays reachable at its physical address until this returns
+    let convert_pointer = unsafe { (*rs.services.as_ptr()).convert_pointer };
+    let convert = |slot: *mut VOID| {
+        let _ = unsafe { convert_pointer(EFI_OPTIONAL_PTR, slot) };
+    };
+
+    let pointers = RUNTIME_POINTERS.lock();
+    for &slot in &pointers.slots[..pointers.len] {
+        convert(slot as *mut VOID);
+    }
+    if let Some(st) = SYSTEM_TABLE.get() {
+        convert(st.table.as_slot() as *mut VOID);
+    }
+    convert(rs.services.as_slot() as *mut VOID);
+}
+
+// Virtual address of `address` under an updated map, if it's runtime memory.
+// A descriptor whose range overflows the address space is skipped.
+fn to_virtual(map: &MemoryMap, address: u64) -> Option<u64> {
+    map.iter()
+        .filter(|desc| desc.attribute & EFI_MEMORY_RUNTIME != 0)
+        .find(|desc| {
+            desc.number_of_pages
+                .checked_mul(EFI_PAGE_SIZE as u64)
+                .and_then(|size| desc.physical_start.checked_add(size))
+                .is_some_and(|end| (desc.physical_start..end).contains(&address))
+        })
+        .and_then(|desc| (address - desc.physical_start).checked_add(desc.virtual_start))
+}
+
+impl RuntimeServices {
+    /// Maps every runtime range of `map` at its physical address plus
+    /// `offset` and switches the firmware to virtual mode.
+    ///
+    /// On success the descriptors in `map` hold the virtual addresses, and
+    /// `SYSTEM_TABLE` and `RUNTIME_SERVICES` point at the virtual tables.
+    ///
+    /// # Safety
+    ///
+    /// Must be called once, after `exit_boot_services`, with the map it
+    /// returned. The new mappings have to be in place before runtime
+    /// services are used again.
+    pub unsafe fn set_virtual_address_map(&self,
+                                          map: &mut MemoryMap,
+                                          offset: u64
+    ) -> Result<(), EfiStatus> {
+        for desc in map.iter_mut() {
+            if desc.attribute & EFI_MEMORY_RUNTIME != 0 {
+                desc.virtual_start = desc.physical_start.wrapping_add(offset);
+            }
+        }
+
+        let services = self.services.as_ptr();
+        let table = SYSTEM_TABLE.get().map(|st| st.table.as_ptr());
+        let status = unsafe {
+            ((*services).set_virtual_address_map)(
+                map.map_size(),
+                map.descriptor_size(),
+                map.descriptor_version(),
+                map.as_mut_ptr(),
+            )
+        };
+        efi_try!(status)?;
+
+        // a registered handler has already converted the globals
+        if let Some(rs) = RUNTIME_SERVICES.get()
+            && rs.services.as_ptr() == services
+            && let Some(virt) = to_virtual(map, services as u64)
+        {
+            rs.services.set(virt as *mut _);
+        }
+        if let (Some(st), Some(table)) = (SYSTEM_TABLE.get(), table)
+            && st.table.as_ptr() == table
+            && let Some(virt) = to_virtual(map, table as u64)
+        {
+            st.table.set(virt as *mut _);
+        }
+        Ok(())
+    }
+}
@@ -1,15 +1,27 @@
-#[repr(transparent)]
-pub struct ThreadSafePtr<T>(*mut T);
+use core::sync::atomic::{AtomicPtr, Ordering};
 
-unsafe impl<T> Send for ThreadSafePtr<T> {}
-unsafe impl<T> Sync for ThreadSafePtr<T> {}
+#[repr(transparent)]
+pub struct ThreadSafePtr<T>(AtomicPtr<T>);
 
 impl<T> ThreadSafePtr<T> {
+    /// # Safety
+    ///
+    /// `ptr` is shared across threads; the pointee must tolerate that.
     pub unsafe fn new(ptr: *mut T) -> Self {
-        ThreadSafePtr(ptr)
+        ThreadSafePtr(AtomicPtr::new(ptr))
     }
 
     pub fn as_ptr(&self) -> *mut T {
-        self.0
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Where the pointer itself is stored, for in-place fixups by
+    /// `convert_pointer`.
+    pub(crate) fn as_slot(&self) -> *mut *mut T {
+        self.0.as_ptr()
+    }
+
+    pub(crate) fn set(&self, ptr: *mut T) {
+        self.0.store(ptr, Ordering::Relaxed);
     }
 }
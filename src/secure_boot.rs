@@ -0,0 +1,454 @@
+//! Secure Boot mode variables and signature databases.
+
+use alloc::vec::Vec;
+
+use crate::bytes::*;
+use crate::tables::rs::RuntimeServices;
+use crate::types::*;
+use crate::variable::*;
+
+pub const EFI_IMAGE_SECURITY_DATABASE_GUID: EfiGuid = EfiGuid {
+    data1: 0xd719b2cb,
+    data2: 0x3d3a,
+    data3: 0x4596,
+    data4: [0xa3,0xbc,0xda,0xd0,0x0e,0x67,0x65,0x6f],
+};
+
+pub const EFI_CERT_SHA256_GUID: EfiGuid = EfiGuid {
+    data1: 0xc1c41626,
+    data2: 0x504c,
+    data3: 0x4092,
+    data4: [0xac,0xa9,0x41,0xf9,0x36,0x93,0x43,0x28],
+};
+
+pub const EFI_CERT_RSA2048_GUID: EfiGuid = EfiGuid {
+    data1: 0x3c5766e8,
+    data2: 0x269c,
+    data3: 0x4e34,
+    data4: [0xaa,0x14,0xed,0x77,0x6e,0x85,0xb3,0xb6],
+};
+
+pub const EFI_CERT_RSA2048_SHA256_GUID: EfiGuid = EfiGuid {
+    data1: 0xe2b36190,
+    data2: 0x879b,
+    data3: 0x4a3d,
+    data4: [0xad,0x8d,0xf2,0xe7,0xbb,0xa3,0x27,0x84],
+};
+
+pub const EFI_CERT_SHA1_GUID: EfiGuid = EfiGuid {
+    data1: 0x826ca512,
+    data2: 0xcf10,
+    data3: 0x4ac9,
+    data4: [0xb1,0x87,0xbe,0x01,0x49,0x66,0x31,0xbd],
+};
+
+pub const EFI_CERT_RSA2048_SHA1_GUID: EfiGuid = EfiGuid {
+    data1: 0x67f8444f,
+    data2: 0x8743,
+    data3: 0x48f1,
+    data4: [0xa3,0x28,0x1e,0xaa,0xb8,0x73,0x60,0x80],
+};
+
+pub const EFI_CERT_X509_GUID: EfiGuid = EfiGuid {
+    data1: 0xa5c059a1,
+    data2: 0x94e4,
+    data3: 0x4aa7,
+    data4: [0x87,0xb5,0xab,0x15,0x5c,0x2b,0xf0,0x72],
+};
+
+pub const EFI_CERT_SHA224_GUID: EfiGuid = EfiGuid {
+    data1: 0x0b6e5233,
+    data2: 0xa65c,
+    data3: 0x44c9,
+    data4: [0x94,0x07,0xd9,0xab,0x83,0xbf,0xc8,0xbd],
+};
+
+pub const EFI_CERT_SHA384_GUID: EfiGuid = EfiGuid {
+    data1: 0xff3e5307,
+    data2: 0x9fd0,
+    data3: 0x48c9,
+    data4: [0x85,0xf1,0x8a,0xd5,0x6c,0x70,0x1e,0x01],
+};
+
+pub const EFI_CERT_SHA512_GUID: EfiGuid = EfiGuid {
+    data1: 0x093e0fae,
+    data2: 0xa6c4,
+    data3: 0x4f50,
+    data4: [0x9f,0x1b,0xd4,0x1e,0x2b,0x89,0xc1,0x9a],
+};
+
+pub const EFI_CERT_X509_SHA256_GUID: EfiGuid = EfiGuid {
+    data1: 0x3bd2a492,
+    data2: 0x96c0,
+    data3: 0x4079,
+    data4: [0xb4,0x20,0xfc,0xf9,0x8e,0xf1,0x03,0xed],
+};
+
+pub const EFI_CERT_X509_SHA384_GUID: EfiGuid = EfiGuid {
+    data1: 0x7076876e,
+    data2: 0x80c2,
+    data3: 0x4ee6,
+    data4: [0xaa,0xd2,0x28,0xb3,0x49,0xa6,0x86,0x5b],
+};
+
+pub const EFI_CERT_X509_SHA512_GUID: EfiGuid = EfiGuid {
+    data1: 0x446dbf63,
+    data2: 0x2502,
+    data3: 0x4cda,
+    data4: [0xbc,0xfa,0x24,0x65,0xd2,0xb0,0xfe,0x9d],
+};
+
+/// Platform Secure Boot mode, from the global mode variables.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SecureBootState {
+    pub secure_boot: bool,
+    pub setup_mode: bool,
+    /// `None` on firmware older than UEFI 2.5.
+    pub audit_mode: Option<bool>,
+    /// `None` on firmware older than UEFI 2.5.
+    pub deployed_mode: Option<bool>,
+}
+
+impl SecureBootState {
+    /// Whether image signatures are actually checked and enforced.
+    pub fn is_enforced(&self) -> bool {
+        self.secure_boot && !self.setup_mode && self.audit_mode != Some(true)
+    }
+}
+
+/// Known `SignatureType` values of an `EFI_SIGNATURE_LIST`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SignatureType {
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Rsa2048,
+    Rsa2048Sha1,
+    Rsa2048Sha256,
+    X509,
+    X509Sha256,
+    X509Sha384,
+    X509Sha512,
+    Unknown(EfiGuid),
+}
+
+impl SignatureType {
+    pub fn from_guid(guid: EfiGuid) -> Self {
+        match guid {
+            EFI_CERT_SHA1_GUID => SignatureType::Sha1,
+            EFI_CERT_SHA224_GUID => SignatureType::Sha224,
+            EFI_CERT_SHA256_GUID => SignatureType::Sha256,
+            EFI_CERT_SHA384_GUID => SignatureType::Sha384,
+            EFI_CERT_SHA512_GUID => SignatureType::Sha512,
+            EFI_CERT_RSA2048_GUID => SignatureType::Rsa2048,
+            EFI_CERT_RSA2048_SHA1_GUID => SignatureType::Rsa2048Sha1,
+            EFI_CERT_RSA2048_SHA256_GUID => SignatureType::Rsa2048Sha256,
+            EFI_CERT_X509_GUID => SignatureType::X509,
+            EFI_CERT_X509_SHA256_GUID => SignatureType::X509Sha256,
+            EFI_CERT_X509_SHA384_GUID => SignatureType::X509Sha384,
+            EFI_CERT_X509_SHA512_GUID => SignatureType::X509Sha512,
+            guid => SignatureType::Unknown(guid),
+        }
+    }
+
+    pub fn guid(&self) -> EfiGuid {
+        match *self {
+            SignatureType::Sha1 => EFI_CERT_SHA1_GUID,
+            SignatureType::Sha224 => EFI_CERT_SHA224_GUID,
+            SignatureType::Sha256 => EFI_CERT_SHA256_GUID,
+            SignatureType::Sha384 => EFI_CERT_SHA384_GUID,
+            SignatureType::Sha512 => EFI_CERT_SHA512_GUID,
+            SignatureType::Rsa2048 => EFI_CERT_RSA2048_GUID,
+            SignatureType::Rsa2048Sha1 => EFI_CERT_RSA2048_SHA1_GUID,
+            SignatureType::Rsa2048Sha256 => EFI_CERT_RSA2048_SHA256_GUID,
+            SignatureType::X509 => EFI_CERT_X509_GUID,
+            SignatureType::X509Sha256 => EFI_CERT_X509_SHA256_GUID,
+            SignatureType::X509Sha384 => EFI_CERT_X509_SHA384_GUID,
+            SignatureType::X509Sha512 => EFI_CERT_X509_SHA512_GUID,
+            SignatureType::Unknown(guid) => guid,
+        }
+    }
+}
+
+/// Signature database variables.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SignatureDb {
+    /// Platform key.
+    Pk,
+    /// Key exchange keys.
+    Kek,
+    /// Allowed signatures.
+    Db,
+    /// Forbidden signatures.
+    Dbx,
+    /// Timestamp signatures.
+    Dbt,
+    /// Recovery signatures.
+    Dbr,
+}
+
+impl SignatureDb {
+    pub fn variable_name(&self) -> &'static str {
+        match self {
+            SignatureDb::Pk => "PK",
+            SignatureDb::Kek => "KEK",
+            SignatureDb::Db => "db",
+            SignatureDb::Dbx => "dbx",
+            SignatureDb::Dbt => "dbt",
+            SignatureDb::Dbr => "dbr",
+        }
+    }
+
+    pub fn vendor_guid(&self) -> EfiGuid {
+        match self {
+            SignatureDb::Pk | SignatureDb::Kek => EFI_GLOBAL_VARIABLE,
+            _ => EFI_IMAGE_SECURITY_DATABASE_GUID,
+        }
+    }
+}
+
+// SignatureType, SignatureListSize, SignatureHeaderSize, SignatureSize
+const SIGNATURE_LIST_HEADER_SIZE: usize = 28;
+// SignatureOwner
+const SIGNATURE_DATA_HEADER_SIZE: usize = 16;
+
+/// One `EFI_SIGNATURE_LIST`: signatures of a single type and size.
+#[derive(Debug, Copy, Clone)]
+pub struct SignatureList<'a> {
+    pub signature_type: SignatureType,
+    /// Type-specific `SignatureHeader`, empty for all defined types.
+    pub header: &'a [u8],
+    signature_size: usize,
+    signatures: &'a [u8],
+}
+
+impl<'a> SignatureList<'a> {
+    /// Parses the list at the start of `bytes`, returning it and its size.
+    fn parse(bytes: &'a [u8]) -> Option<(Self, usize)> {
+        let guid = EfiGuid::from_bytes(read_array(bytes, 0)?);
+        let list_size = read_u32(bytes, 16)? as usize;
+        let header_size = read_u32(bytes, 20)? as usize;
+        let signature_size = read_u32(bytes, 24)? as usize;
+
+        let list = bytes.get(..list_size)?;
+        let header_end = SIGNATURE_LIST_HEADER_SIZE.checked_add(header_size)?;
+        let header = list.get(SIGNATURE_LIST_HEADER_SIZE..header_end)?;
+        let signatures = &list[header_end..];
+        if signature_size < SIGNATURE_DATA_HEADER_SIZE
+            || !signatures.len().is_multiple_of(signature_size)
+        {
+            return None;
+        }
+
+        let parsed = SignatureList {
+            signature_type: SignatureType::from_guid(guid),
+            header,
+            signature_size,
+            signatures,
+        };
+        Some((parsed, list_size))
+    }
+
+    /// Size of each `EFI_SIGNATURE_DATA`, including the owner GUID.
+    pub fn signature_size(&self) -> usize {
+        self.signature_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.signatures.len() / self.signature_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    pub fn signatures(&self) -> impl Iterator<Item = SignatureData<'a>> + use<'a> {
+        self.signatures.chunks_exact(self.signature_size).map(|chunk| SignatureData {
+            owner: EfiGuid::from_bytes(chunk[..16].try_into().unwrap()),
+            data: &chunk[SIGNATURE_DATA_HEADER_SIZE..],
+        })
+    }
+}
+
+/// One `EFI_SIGNATURE_DATA` entry.
+#[derive(Debug, Copy, Clone)]
+pub struct SignatureData<'a> {
+    pub owner: EfiGuid,
+    /// A hash, key or DER certificate, depending on the list type.
+    pub data: &'a [u8],
+}
+
+/// Validated contents of a signature database variable.
+#[derive(Debug, Clone, Default)]
+pub struct SignatureDatabase {
+    data: Vec<u8>,
+}
+
+impl SignatureDatabase {
+    /// Checks that `data` is a sequence of well-formed signature lists.
+    pub fn parse(data: Vec<u8>) -> Option<Self> {
+        let mut rest = data.as_slice();
+        while !rest.is_empty() {
+            let (_, size) = SignatureList::parse(rest)?;
+            rest = &rest[size..];
+        }
+        Some(SignatureDatabase { data })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn lists(&self) -> impl Iterator<Item = SignatureList<'_>> {
+        let mut rest = self.data.as_slice();
+        core::iter::from_fn(move || {
+            // validated in `parse`
+            let (list, size) = SignatureList::parse(rest)?;
+            rest = &rest[size..];
+            Some(list)
+        })
+    }
+
+    /// All signatures with their list's type.
+    pub fn signatures(&self) -> impl Iterator<Item = (SignatureType, SignatureData<'_>)> {
+        self.lists().flat_map(|list| {
+            list.signatures().map(move |sig| (list.signature_type, sig))
+        })
+    }
+
+    /// Whether an entry of `signature_type` holds exactly `data`, e.g. a
+    /// revoked SHA-256 image hash in `dbx`.
+    pub fn contains(&self, signature_type: SignatureType, data: &[u8]) -> bool {
+        self.signatures().any(|(ty, sig)| ty == signature_type && sig.data == data)
+    }
+}
+
+impl RuntimeServices {
+    fn get_mode_variable(&self, name: &str) -> Result<Option<bool>, EfiStatus> {
+        match self.get_variable(name, &EFI_GLOBAL_VARIABLE) {
+            Ok((data, _)) => Ok(Some(data.first().is_some_and(|&v| v == 1))),
+            Err(EfiStatus::NOT_FOUND) => Ok(None),
+            Err(status) => Err(status),
+        }
+    }
+
+    /// Reads `SecureBoot`, `SetupMode`, `AuditMode` and `DeployedMode`.
+    /// Missing `SecureBoot` or `SetupMode` read as disabled.
+    pub fn secure_boot_state(&self) -> Result<SecureBootState, EfiStatus> {
+        Ok(SecureBootState {
+            secure_boot: self.get_mode_variable("SecureBoot")?.unwrap_or(false),
+            setup_mode: self.get_mode_variable("SetupMode")?.unwrap_or(false),
+            audit_mode: self.get_mode_variable("AuditMode")?,
+            deployed_mode: self.get_mode_variable("DeployedMode")?,
+        })
+    }
+
+    /// Reads and parses a signature database; a missing variable reads as
+    /// empty. Malformed contents are reported as
+    /// `EfiStatus::VOLUME_CORRUPTED`.
+    pub fn signature_database(&self, db: SignatureDb) -> Result<SignatureDatabase, EfiStatus> {
+        match self.get_variable(db.variable_name(), &db.vendor_guid()) {
+            Ok((data, _)) => SignatureDatabase::parse(data).ok_or(EfiStatus::VOLUME_CORRUPTED),
+            Err(EfiStatus::NOT_FOUND) => Ok(SignatureDatabase::default()),
+            Err(status) => Err(status),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNER: EfiGuid = EfiGuid {
+        data1: 0x77fa9abd,
+        data2: 0x0359,
+        data3: 0x4d32,
+        data4: [0xbd,0x60,0x28,0xf4,0xe7,0x8f,0x78,0x4b],
+    };
+
+    fn signature_list(ty: EfiGuid, signature_size: u32, entries: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = alloc::vec![0u8; SIGNATURE_LIST_HEADER_SIZE];
+        bytes[..16].copy_from_slice(&ty.to_bytes());
+        write_u32(&mut bytes, 24, signature_size);
+        for entry in entries {
+            bytes.extend_from_slice(&OWNER.to_bytes());
+            bytes.extend_from_slice(entry);
+        }
+        let list_size = bytes.len() as u32;
+        write_u32(&mut bytes, 16, list_size);
+        bytes
+    }
+
+    fn sample_db() -> SignatureDatabase {
+        let mut data = signature_list(EFI_CERT_SHA256_GUID, 48, &[&[1; 32], &[2; 32]]);
+        data.extend(signature_list(EFI_CERT_X509_GUID, 21, &[b"certs"]));
+        SignatureDatabase::parse(data).unwrap()
+    }
+
+    #[test]
+    fn parse_lists_in_order() {
+        let db = sample_db();
+        let lists: Vec<_> = db.lists().collect();
+        assert_eq!(lists.len(), 2);
+        assert_eq!(lists[0].signature_type, SignatureType::Sha256);
+        assert_eq!(lists[0].len(), 2);
+        assert!(lists[0].header.is_empty());
+        assert_eq!(lists[1].signature_type, SignatureType::X509);
+        assert_eq!(lists[1].signature_size(), 21);
+    }
+
+    #[test]
+    fn signatures_carry_owner_and_data() {
+        let db = sample_db();
+        let sig = db.lists().next().unwrap().signatures().nth(1).unwrap();
+        assert_eq!(sig.owner, OWNER);
+        assert_eq!(sig.data, &[2; 32]);
+    }
+
+    #[test]
+    fn contains_matches_type_and_data() {
+        let db = sample_db();
+        assert!(db.contains(SignatureType::Sha256, &[1; 32]));
+        assert!(!db.contains(SignatureType::Sha256, &[3; 32]));
+        assert!(!db.contains(SignatureType::Sha1, &[1; 32]));
+    }
+
+    #[test]
+    fn parse_rejects_truncated_list() {
+        let data = signature_list(EFI_CERT_SHA256_GUID, 48, &[&[1; 32]]);
+        assert!(SignatureDatabase::parse(data[..data.len() - 1].to_vec()).is_none());
+    }
+
+    #[test]
+    fn parse_rejects_truncated_header() {
+        let data = signature_list(EFI_CERT_SHA256_GUID, 48, &[&[1; 32]]);
+        assert!(SignatureDatabase::parse(data[..20].to_vec()).is_none());
+    }
+
+    #[test]
+    fn parse_rejects_signature_smaller_than_owner() {
+        let small = signature_list(EFI_CERT_SHA256_GUID, 15, &[&[1; 32]]);
+        assert!(SignatureDatabase::parse(small).is_none());
+    }
+
+    #[test]
+    fn parse_rejects_zero_signature_size() {
+        let zero = signature_list(EFI_CERT_SHA256_GUID, 0, &[]);
+        assert!(SignatureDatabase::parse(zero).is_none());
+    }
+
+    #[test]
+    fn parse_rejects_uneven_entries() {
+        let uneven = signature_list(EFI_CERT_SHA256_GUID, 40, &[&[1; 32]]);
+        assert!(SignatureDatabase::parse(uneven).is_none());
+    }
+
+    #[test]
+    fn parse_rejects_list_size_below_header() {
+        let mut short = signature_list(EFI_CERT_SHA256_GUID, 48, &[]);
+        write_u32(&mut short, 16, 0);
+        assert!(SignatureDatabase::parse(short).is_none());
+    }
+}
@@ -0,0 +1,930 @@
+//! SMBIOS entry points and structure table parsing.
+//!
+//! The parsers work on byte slices. [`Smbios`] reads the structure table in
+//! memory, found through the SMBIOS3 or SMBIOS configuration table entry.
+
+use crate::bytes::*;
+use crate::tables::config::*;
+use crate::tables::st::SystemTable;
+use crate::types::*;
+
+pub const SMBIOS2_ANCHOR: &[u8; 4] = b"_SM_";
+pub const SMBIOS3_ANCHOR: &[u8; 5] = b"_SM3_";
+const SMBIOS2_INTERMEDIATE_ANCHOR: &[u8; 5] = b"_DMI_";
+
+pub const BIOS_INFORMATION: u8 = 0;
+pub const SYSTEM_INFORMATION: u8 = 1;
+pub const BASEBOARD_INFORMATION: u8 = 2;
+pub const SYSTEM_ENCLOSURE: u8 = 3;
+pub const PROCESSOR_INFORMATION: u8 = 4;
+pub const MEMORY_DEVICE: u8 = 17;
+pub const END_OF_TABLE: u8 = 127;
+
+// type, length, handle
+const STRUCTURE_HEADER_SIZE: usize = 4;
+
+/// A validated 2.x (`_SM_`) or 3.x (`_SM3_`) entry point.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SmbiosEntryPoint {
+    pub major_version: u8,
+    pub minor_version: u8,
+    pub table_address: u64,
+    /// Exact length for 2.x; an upper bound for 3.x.
+    pub table_length: u32,
+    /// Only recorded by 2.x entry points.
+    pub structure_count: Option<u16>,
+}
+
+impl SmbiosEntryPoint {
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(SMBIOS3_ANCHOR) {
+            let length = read_u8(bytes, 6)? as usize;
+            if length < 0x18 || !checksum_ok(bytes.get(..length)?) {
+                return None;
+            }
+            Some(SmbiosEntryPoint {
+                major_version: read_u8(bytes, 7)?,
+                minor_version: read_u8(bytes, 8)?,
+                table_address: read_u64(bytes, 16)?,
+                table_length: read_u32(bytes, 12)?,
+                structure_count: None,
+            })
+        } else if bytes.starts_with(SMBIOS2_ANCHOR) {
+            let length = smbios2_length(read_u8(bytes, 5)?);
+            if length < 0x1f
+                || !checksum_ok(bytes.get(..length)?)
+                || bytes.get(16..21)? != SMBIOS2_INTERMEDIATE_ANCHOR
+                || !checksum_ok(bytes.get(16..31)?)
+            {
+                return None;
+            }
+            Some(SmbiosEntryPoint {
+                major_version: read_u8(bytes, 6)?,
+                minor_version: read_u8(bytes, 7)?,
+                table_address: read_u32(bytes, 24)? as u64,
+                table_length: read_u16(bytes, 22)? as u32,
+                structure_count: Some(read_u16(bytes, 28)?),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` must point at an SMBIOS entry point.
+    pub unsafe fn from_ptr(ptr: *const VOID) -> Option<Self> {
+        let ptr = ptr as *const u8;
+        // the length byte sits at 5 for 2.x and 6 for 3.x
+        let head = unsafe { core::slice::from_raw_parts(ptr, 7) };
+        let length = if head.starts_with(SMBIOS3_ANCHOR) {
+            head[6] as usize
+        } else {
+            smbios2_length(head[5])
+        };
+        SmbiosEntryPoint::parse(unsafe { core::slice::from_raw_parts(ptr, length) })
+    }
+}
+
+// SMBIOS 2.1 gave the entry point length as 0x1e, though the structure was
+// always 0x1f bytes; firmware copied that, so like dmidecode we read it as
+// 0x1f and check the checksum over all of it.
+fn smbios2_length(length: u8) -> usize {
+    match length {
+        0x1e => 0x1f,
+        length => length as usize,
+    }
+}
+
+/// One structure: its formatted area and string set.
+#[derive(Debug, Copy, Clone)]
+pub struct Structure<'a> {
+    formatted: &'a [u8],
+    strings: &'a [u8],
+}
+
+impl<'a> Structure<'a> {
+    pub fn structure_type(&self) -> u8 {
+        self.formatted[0]
+    }
+
+    pub fn handle(&self) -> u16 {
+        read_u16(self.formatted, 2).unwrap()
+    }
+
+    /// The formatted area, header included, so that offsets match the
+    /// specification.
+    pub fn formatted(&self) -> &'a [u8] {
+        self.formatted
+    }
+
+    /// The strings of the string set, in order.
+    pub fn strings(&self) -> impl Iterator<Item = &'a [u8]> + 'a {
+        let strings = self.strings;
+        strings.split(|&b| b == 0).filter(move |_| !strings.is_empty())
+    }
+
+    /// String number `index`, counting from 1. Index 0 means no string.
+    pub fn string(&self, index: u8) -> Option<&'a str> {
+        let index = (index as usize).checked_sub(1)?;
+        core::str::from_utf8(self.strings().nth(index)?).ok()
+    }
+
+    /// The string referenced by the byte at `offset`.
+    pub fn string_at(&self, offset: usize) -> Option<&'a str> {
+        self.string(self.byte(offset)?)
+    }
+
+    pub fn byte(&self, offset: usize) -> Option<u8> {
+        read_u8(self.formatted, offset)
+    }
+
+    pub fn word(&self, offset: usize) -> Option<u16> {
+        read_u16(self.formatted, offset)
+    }
+
+    pub fn dword(&self, offset: usize) -> Option<u32> {
+        read_u32(self.formatted, offset)
+    }
+
+    pub fn qword(&self, offset: usize) -> Option<u64> {
+        read_u64(self.formatted, offset)
+    }
+}
+
+/// Iterator over a structure table. Stops after the end-of-table structure
+/// or at the first malformed one.
+#[derive(Debug, Clone)]
+pub struct Structures<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Structures<'a> {
+    pub fn new(table: &'a [u8]) -> Self {
+        Structures { rest: table }
+    }
+}
+
+impl<'a> Iterator for Structures<'a> {
+    type Item = Structure<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let length = read_u8(self.rest, 1)? as usize;
+        if length < STRUCTURE_HEADER_SIZE || length > self.rest.len() {
+            self.rest = &[];
+            return None;
+        }
+        let (formatted, trailer) = self.rest.split_at(length);
+        // the string set ends with a double null, which is all there is
+        // for a structure without strings
+        let Some(end) = trailer.windows(2).position(|pair| pair == [0, 0]) else {
+            self.rest = &[];
+            return None;
+        };
+        let structure = Structure { formatted, strings: &trailer[..end] };
+        self.rest = if structure.structure_type() == END_OF_TABLE {
+            &[]
+        } else {
+            &trailer[end + 2..]
+        };
+        Some(structure)
+    }
+}
+
+macro_rules! structure_view {
+    ($(#[$meta:meta])* $name:ident, $ty:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Copy, Clone)]
+        pub struct $name<'a> {
+            structure: Structure<'a>,
+        }
+
+        impl<'a> $name<'a> {
+            /// `None` if `structure` is of another type or too short.
+            pub fn new(structure: Structure<'a>) -> Option<Self> {
+                let valid = structure.structure_type() == $ty
+                    && structure.formatted.len() >= Self::MIN_LENGTH;
+                valid.then_some($name { structure })
+            }
+
+            pub fn structure(&self) -> &Structure<'a> {
+                &self.structure
+            }
+        }
+    };
+}
+
+structure_view!(
+    /// BIOS Information (type 0).
+    BiosInformation, BIOS_INFORMATION
+);
+
+impl<'a> BiosInformation<'a> {
+    const MIN_LENGTH: usize = 0x12;
+
+    pub fn vendor(&self) -> Option<&'a str> {
+        self.structure.string_at(0x04)
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.structure.string_at(0x05)
+    }
+
+    pub fn starting_address_segment(&self) -> u16 {
+        self.structure.word(0x06).unwrap()
+    }
+
+    pub fn release_date(&self) -> Option<&'a str> {
+        self.structure.string_at(0x08)
+    }
+
+    /// ROM size in bytes, using the extended size from 3.1 when needed.
+    pub fn rom_size(&self) -> u64 {
+        let size = self.structure.byte(0x09).unwrap();
+        match self.structure.word(0x18) {
+            Some(extended) if size == 0xff => {
+                let units = (extended & 0x3fff) as u64;
+                match extended >> 14 {
+                    0 => units << 20,
+                    _ => units << 30,
+                }
+            }
+            _ => (size as u64 + 1) << 16,
+        }
+    }
+
+    pub fn characteristics(&self) -> u64 {
+        self.structure.qword(0x0a).unwrap()
+    }
+
+    /// System BIOS major and minor release, from 2.4.
+    pub fn bios_release(&self) -> Option<(u8, u8)> {
+        Some((self.structure.byte(0x14)?, self.structure.byte(0x15)?))
+    }
+
+    /// Embedded controller firmware major and minor release, from 2.4.
+    pub fn ec_release(&self) -> Option<(u8, u8)> {
+        Some((self.structure.byte(0x16)?, self.structure.byte(0x17)?))
+    }
+}
+
+structure_view!(
+    /// System Information (type 1).
+    SystemInformation, SYSTEM_INFORMATION
+);
+
+impl<'a> SystemInformation<'a> {
+    const MIN_LENGTH: usize = 0x08;
+
+    pub fn manufacturer(&self) -> Option<&'a str> {
+        self.structure.string_at(0x04)
+    }
+
+    pub fn product_name(&self) -> Option<&'a str> {
+        self.structure.string_at(0x05)
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.structure.string_at(0x06)
+    }
+
+    pub fn serial_number(&self) -> Option<&'a str> {
+        self.structure.string_at(0x07)
+    }
+
+    /// The system UUID, from 2.1. The first three fields are little-endian
+    /// from 2.6 on, the same layout as [`EfiGuid`].
+    pub fn uuid(&self) -> Option<EfiGuid> {
+        Some(EfiGuid::from_bytes(read_array(self.structure.formatted, 0x08)?))
+    }
+
+    pub fn wake_up_type(&self) -> Option<u8> {
+        self.structure.byte(0x18)
+    }
+
+    pub fn sku_number(&self) -> Option<&'a str> {
+        self.structure.string_at(0x19)
+    }
+
+    pub fn family(&self) -> Option<&'a str> {
+        self.structure.string_at(0x1a)
+    }
+}
+
+structure_view!(
+    /// Baseboard Information (type 2).
+    BaseboardInformation, BASEBOARD_INFORMATION
+);
+
+impl<'a> BaseboardInformation<'a> {
+    const MIN_LENGTH: usize = 0x08;
+
+    pub fn manufacturer(&self) -> Option<&'a str> {
+        self.structure.string_at(0x04)
+    }
+
+    pub fn product(&self) -> Option<&'a str> {
+        self.structure.string_at(0x05)
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.structure.string_at(0x06)
+    }
+
+    pub fn serial_number(&self) -> Option<&'a str> {
+        self.structure.string_at(0x07)
+    }
+
+    pub fn asset_tag(&self) -> Option<&'a str> {
+        self.structure.string_at(0x08)
+    }
+
+    pub fn feature_flags(&self) -> Option<u8> {
+        self.structure.byte(0x09)
+    }
+
+    pub fn location_in_chassis(&self) -> Option<&'a str> {
+        self.structure.string_at(0x0a)
+    }
+
+    pub fn chassis_handle(&self) -> Option<u16> {
+        self.structure.word(0x0b)
+    }
+
+    pub fn board_type(&self) -> Option<u8> {
+        self.structure.byte(0x0d)
+    }
+}
+
+structure_view!(
+    /// System Enclosure or Chassis (type 3).
+    ChassisInformation, SYSTEM_ENCLOSURE
+);
+
+impl<'a> ChassisInformation<'a> {
+    const MIN_LENGTH: usize = 0x09;
+
+    pub fn manufacturer(&self) -> Option<&'a str> {
+        self.structure.string_at(0x04)
+    }
+
+    /// Enclosure type, without the lock-present bit.
+    pub fn chassis_type(&self) -> u8 {
+        self.structure.byte(0x05).unwrap() & 0x7f
+    }
+
+    pub fn has_lock(&self) -> bool {
+        self.structure.byte(0x05).unwrap() & 0x80 != 0
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.structure.string_at(0x06)
+    }
+
+    pub fn serial_number(&self) -> Option<&'a str> {
+        self.structure.string_at(0x07)
+    }
+
+    pub fn asset_tag(&self) -> Option<&'a str> {
+        self.structure.string_at(0x08)
+    }
+
+    pub fn boot_up_state(&self) -> Option<u8> {
+        self.structure.byte(0x09)
+    }
+
+    pub fn power_supply_state(&self) -> Option<u8> {
+        self.structure.byte(0x0a)
+    }
+
+    pub fn thermal_state(&self) -> Option<u8> {
+        self.structure.byte(0x0b)
+    }
+
+    pub fn security_status(&self) -> Option<u8> {
+        self.structure.byte(0x0c)
+    }
+
+    /// From 2.7; it follows the variable-length contained elements.
+    pub fn sku_number(&self) -> Option<&'a str> {
+        let count = self.structure.byte(0x13)? as usize;
+        let record_length = self.structure.byte(0x14)? as usize;
+        self.structure.string_at(0x15 + count * record_length)
+    }
+}
+
+structure_view!(
+    /// Processor Information (type 4).
+    ProcessorInformation, PROCESSOR_INFORMATION
+);
+
+impl<'a> ProcessorInformation<'a> {
+    const MIN_LENGTH: usize = 0x1a;
+
+    pub fn socket_designation(&self) -> Option<&'a str> {
+        self.structure.string_at(0x04)
+    }
+
+    pub fn processor_type(&self) -> u8 {
+        self.structure.byte(0x05).unwrap()
+    }
+
+    /// Processor family, taking the 2.6 extension into account.
+    pub fn family(&self) -> u16 {
+        match self.structure.byte(0x06).unwrap() {
+            0xfe => self.structure.word(0x28).unwrap_or(0xfe),
+            family => family as u16,
+        }
+    }
+
+    pub fn manufacturer(&self) -> Option<&'a str> {
+        self.structure.string_at(0x07)
+    }
+
+    /// Raw processor ID, e.g. CPUID leaf 1 EAX and EDX on x86.
+    pub fn processor_id(&self) -> u64 {
+        self.structure.qword(0x08).unwrap()
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.structure.string_at(0x10)
+    }
+
+    pub fn voltage(&self) -> u8 {
+        self.structure.byte(0x11).unwrap()
+    }
+
+    /// External clock in MHz, 0 if unknown.
+    pub fn external_clock(&self) -> u16 {
+        self.structure.word(0x12).unwrap()
+    }
+
+    /// Maximum speed in MHz, 0 if unknown.
+    pub fn max_speed(&self) -> u16 {
+        self.structure.word(0x14).unwrap()
+    }
+
+    /// Current speed in MHz, 0 if unknown.
+    pub fn current_speed(&self) -> u16 {
+        self.structure.word(0x16).unwrap()
+    }
+
+    pub fn status(&self) -> u8 {
+        self.structure.byte(0x18).unwrap()
+    }
+
+    pub fn is_socket_populated(&self) -> bool {
+        self.status() & 0x40 != 0
+    }
+
+    pub fn upgrade(&self) -> u8 {
+        self.structure.byte(0x19).unwrap()
+    }
+
+    pub fn serial_number(&self) -> Option<&'a str> {
+        self.structure.string_at(0x20)
+    }
+
+    pub fn asset_tag(&self) -> Option<&'a str> {
+        self.structure.string_at(0x21)
+    }
+
+    pub fn part_number(&self) -> Option<&'a str> {
+        self.structure.string_at(0x22)
+    }
+
+    // counts past 254 live in the 3.0 word fields
+    fn count(&self, offset: usize, extended: usize) -> Option<u16> {
+        match self.structure.byte(offset)? {
+            0xff => self.structure.word(extended),
+            count => Some(count as u16),
+        }
+    }
+
+    pub fn core_count(&self) -> Option<u16> {
+        self.count(0x23, 0x2a)
+    }
+
+    pub fn cores_enabled(&self) -> Option<u16> {
+        self.count(0x24, 0x2c)
+    }
+
+    pub fn thread_count(&self) -> Option<u16> {
+        self.count(0x25, 0x2e)
+    }
+
+    pub fn characteristics(&self) -> Option<u16> {
+        self.structure.word(0x26)
+    }
+}
+
+structure_view!(
+    /// Memory Device (type 17).
+    MemoryDevice, MEMORY_DEVICE
+);
+
+impl<'a> MemoryDevice<'a> {
+    const MIN_LENGTH: usize = 0x15;
+
+    pub fn physical_memory_array_handle(&self) -> u16 {
+        self.structure.word(0x04).unwrap()
+    }
+
+    /// Total and data width in bits, `None` where unknown.
+    pub fn total_width(&self) -> Option<u16> {
+        self.structure.word(0x08).filter(|&width| width != 0xffff)
+    }
+
+    pub fn data_width(&self) -> Option<u16> {
+        self.structure.word(0x0a).filter(|&width| width != 0xffff)
+    }
+
+    /// Whether a module is installed in this slot.
+    pub fn is_installed(&self) -> bool {
+        self.structure.word(0x0c).unwrap() != 0
+    }
+
+    /// Installed size in bytes; `None` for an empty slot or an unknown
+    /// size.
+    pub fn size(&self) -> Option<u64> {
+        match self.structure.word(0x0c).unwrap() {
+            0 | 0xffff => None,
+            // the real size, in MiB, is in the 2.7 extended field
+            0x7fff => Some(((self.structure.dword(0x1c)? & 0x7fff_ffff) as u64) << 20),
+            size if size & 0x8000 != 0 => Some(((size & 0x7fff) as u64) << 10),
+            size => Some((size as u64) << 20),
+        }
+    }
+
+    pub fn form_factor(&self) -> u8 {
+        self.structure.byte(0x0e).unwrap()
+    }
+
+    pub fn device_locator(&self) -> Option<&'a str> {
+        self.structure.string_at(0x10)
+    }
+
+    pub fn bank_locator(&self) -> Option<&'a str> {
+        self.structure.string_at(0x11)
+    }
+
+    /// Memory type, e.g. 0x1a for DDR4 or 0x22 for DDR5.
+    pub fn memory_type(&self) -> u8 {
+        self.structure.byte(0x12).unwrap()
+    }
+
+    pub fn type_detail(&self) -> u16 {
+        self.structure.word(0x13).unwrap()
+    }
+
+    /// Maximum speed in MT/s, from 2.3.
+    pub fn speed(&self) -> Option<u16> {
+        self.structure.word(0x15).filter(|&speed| speed != 0)
+    }
+
+    pub fn manufacturer(&self) -> Option<&'a str> {
+        self.structure.string_at(0x17)
+    }
+
+    pub fn serial_number(&self) -> Option<&'a str> {
+        self.structure.string_at(0x18)
+    }
+
+    pub fn asset_tag(&self) -> Option<&'a str> {
+        self.structure.string_at(0x19)
+    }
+
+    pub fn part_number(&self) -> Option<&'a str> {
+        self.structure.string_at(0x1a)
+    }
+
+    /// Configured speed in MT/s, from 2.7.
+    pub fn configured_speed(&self) -> Option<u16> {
+        self.structure.word(0x20).filter(|&speed| speed != 0)
+    }
+}
+
+/// The SMBIOS structure table in memory.
+#[derive(Debug, Copy, Clone)]
+pub struct Smbios {
+    entry_point: SmbiosEntryPoint,
+    table: &'static [u8],
+}
+
+impl Smbios {
+    /// # Safety
+    ///
+    /// `entry_point` must point at the platform's SMBIOS entry point, with
+    /// the structure table mapped at its physical address for the rest of
+    /// the program.
+    pub unsafe fn from_entry_point(entry_point: *const VOID) -> Option<Self> {
+        let entry_point = unsafe { SmbiosEntryPoint::from_ptr(entry_point)? };
+        if entry_point.table_address == 0 {
+            return None;
+        }
+        let table = unsafe {
+            core::slice::from_raw_parts(entry_point.table_address as *const u8,
+                                        entry_point.table_length as usize)
+        };
+        Some(Smbios { entry_point, table })
+    }
+
+    pub fn entry_point(&self) -> &SmbiosEntryPoint {
+        &self.entry_point
+    }
+
+    /// SMBIOS major and minor version.
+    pub fn version(&self) -> (u8, u8) {
+        (self.entry_point.major_version, self.entry_point.minor_version)
+    }
+
+    pub fn structures(&self) -> impl Iterator<Item = Structure<'static>> {
+        let count = self.entry_point.structure_count.map_or(usize::MAX, usize::from);
+        Structures::new(self.table).take(count)
+    }
+
+    /// Every structure of type `structure_type`.
+    pub fn find(&self, structure_type: u8) -> impl Iterator<Item = Structure<'static>> {
+        self.structures().filter(move |s| s.structure_type() == structure_type)
+    }
+
+    /// The structure with `handle`, e.g. one referenced by another
+    /// structure.
+    pub fn find_by_handle(&self, handle: u16) -> Option<Structure<'static>> {
+        self.structures().find(|s| s.handle() == handle)
+    }
+
+    pub fn bios_information(&self) -> Option<BiosInformation<'static>> {
+        self.find(BIOS_INFORMATION).find_map(BiosInformation::new)
+    }
+
+    pub fn system_information(&self) -> Option<SystemInformation<'static>> {
+        self.find(SYSTEM_INFORMATION).find_map(SystemInformation::new)
+    }
+
+    pub fn baseboards(&self) -> impl Iterator<Item = BaseboardInformation<'static>> {
+        self.find(BASEBOARD_INFORMATION).filter_map(BaseboardInformation::new)
+    }
+
+    pub fn chassis(&self) -> impl Iterator<Item = ChassisInformation<'static>> {
+        self.find(SYSTEM_ENCLOSURE).filter_map(ChassisInformation::new)
+    }
+
+    pub fn processors(&self) -> impl Iterator<Item = ProcessorInformation<'static>> {
+        self.find(PROCESSOR_INFORMATION).filter_map(ProcessorInformation::new)
+    }
+
+    pub fn memory_devices(&self) -> impl Iterator<Item = MemoryDevice<'static>> {
+        self.find(MEMORY_DEVICE).filter_map(MemoryDevice::new)
+    }
+}
+
+impl SystemTable {
+    /// The SMBIOS tables, preferring the 3.x entry point.
+    pub fn smbios(&self) -> Option<Smbios> {
+        [SMBIOS3_TABLE_GUID, SMBIOS_TABLE_GUID]
+            .iter()
+            .filter_map(|guid| self.find_config_table(guid))
+            .find_map(|entry_point| unsafe { Smbios::from_entry_point(entry_point) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    // `formatted` is the whole formatted area; the header is filled in here
+    fn structure(table: &mut Vec<u8>, ty: u8, handle: u16, formatted: &[u8], strings: &[&str]) {
+        let start = table.len();
+        table.extend_from_slice(formatted);
+        table[start] = ty;
+        table[start + 1] = formatted.len() as u8;
+        write_u16(table, start + 2, handle);
+        for s in strings {
+            table.extend_from_slice(s.as_bytes());
+            table.push(0);
+        }
+        if strings.is_empty() {
+            table.push(0);
+        }
+        table.push(0);
+    }
+
+    fn sample_table() -> Vec<u8> {
+        let mut table = Vec::new();
+
+        let mut bios = alloc::vec![0u8; 0x16];
+        bios[0x04] = 1;
+        bios[0x05] = 2;
+        bios[0x08] = 3;
+        bios[0x09] = 0x0f;
+        bios[0x14] = 1;
+        bios[0x15] = 9;
+        structure(&mut table, BIOS_INFORMATION, 0, &bios, &["Vendor", "1.0", "01/02/2024"]);
+
+        let mut system = alloc::vec![0u8; 0x1b];
+        system[0x04] = 1;
+        system[0x05] = 2;
+        system[0x07] = 3;
+        system[0x08..0x18].copy_from_slice(&[
+            0x33, 0x22, 0x11, 0x00, 0x55, 0x44, 0x77, 0x66,
+            0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff,
+        ]);
+        structure(&mut table, SYSTEM_INFORMATION, 1, &system, &["Maker", "Box", "SN123"]);
+
+        // an unknown type without strings
+        structure(&mut table, 0xc0, 2, &[0, 0, 0, 0, 1, 2], &[]);
+
+        let mut cpu = alloc::vec![0u8; 0x30];
+        cpu[0x04] = 1;
+        cpu[0x06] = 0xfe;
+        write_u16(&mut cpu, 0x16, 3000);
+        cpu[0x18] = 0x41;
+        cpu[0x23] = 0xff;
+        cpu[0x24] = 8;
+        write_u16(&mut cpu, 0x28, 0x0107);
+        write_u16(&mut cpu, 0x2a, 288);
+        structure(&mut table, PROCESSOR_INFORMATION, 3, &cpu, &["CPU0"]);
+
+        let mut dimm = alloc::vec![0u8; 0x22];
+        write_u16(&mut dimm, 0x0c, 0x7fff);
+        dimm[0x10] = 1;
+        dimm[0x12] = 0x22;
+        write_u32(&mut dimm, 0x1c, 65536);
+        structure(&mut table, MEMORY_DEVICE, 4, &dimm, &["DIMM_A1"]);
+
+        let mut empty = alloc::vec![0u8; 0x15];
+        empty[0x10] = 1;
+        structure(&mut table, MEMORY_DEVICE, 5, &empty, &["DIMM_A2"]);
+
+        structure(&mut table, END_OF_TABLE, 6, &[0; 4], &[]);
+        table
+    }
+
+    fn nth(table: &[u8], index: usize) -> Structure<'_> {
+        Structures::new(table).nth(index).unwrap()
+    }
+
+    fn entry_point3() -> Vec<u8> {
+        let mut v3 = alloc::vec![0u8; 0x18];
+        v3[..5].copy_from_slice(SMBIOS3_ANCHOR);
+        v3[6] = 0x18;
+        v3[7] = 3;
+        v3[8] = 6;
+        write_u32(&mut v3, 12, 0x1000);
+        write_u64(&mut v3, 16, 0x7f00_0000);
+        fix_checksum(&mut v3, 5);
+        v3
+    }
+
+    // `length` is the value of the length byte; the structure is 0x1f bytes
+    fn entry_point2(length: u8) -> Vec<u8> {
+        let mut v2 = alloc::vec![0u8; 0x1f];
+        v2[..4].copy_from_slice(SMBIOS2_ANCHOR);
+        v2[5] = length;
+        v2[6] = 2;
+        v2[7] = 8;
+        v2[16..21].copy_from_slice(SMBIOS2_INTERMEDIATE_ANCHOR);
+        write_u16(&mut v2, 22, 0x200);
+        write_u32(&mut v2, 24, 0x000f_0000);
+        write_u16(&mut v2, 28, 12);
+        fix_checksum(&mut v2[16..31], 5);
+        fix_checksum(&mut v2, 4);
+        v2
+    }
+
+    #[test]
+    fn smbios3_entry_point() {
+        let entry = SmbiosEntryPoint::parse(&entry_point3()).unwrap();
+        assert_eq!((entry.major_version, entry.minor_version), (3, 6));
+        assert_eq!(entry.table_address, 0x7f00_0000);
+        assert_eq!(entry.table_length, 0x1000);
+        assert_eq!(entry.structure_count, None);
+    }
+
+    #[test]
+    fn smbios3_entry_point_rejects_bad_checksum() {
+        let mut v3 = entry_point3();
+        v3[16] ^= 1;
+        assert!(SmbiosEntryPoint::parse(&v3).is_none());
+    }
+
+    #[test]
+    fn smbios2_entry_point() {
+        let entry = SmbiosEntryPoint::parse(&entry_point2(0x1f)).unwrap();
+        assert_eq!((entry.major_version, entry.minor_version), (2, 8));
+        assert_eq!(entry.table_address, 0xf_0000);
+        assert_eq!(entry.table_length, 0x200);
+        assert_eq!(entry.structure_count, Some(12));
+    }
+
+    #[test]
+    fn smbios2_entry_point_accepts_length_0x1e() {
+        let v2 = entry_point2(0x1e);
+        assert_eq!(SmbiosEntryPoint::parse(&v2), SmbiosEntryPoint::parse(&entry_point2(0x1f)));
+        assert!(unsafe { SmbiosEntryPoint::from_ptr(v2.as_ptr() as *const VOID) }.is_some());
+    }
+
+    #[test]
+    fn smbios2_entry_point_rejects_short_length() {
+        assert!(SmbiosEntryPoint::parse(&entry_point2(0x1d)).is_none());
+    }
+
+    #[test]
+    fn smbios2_entry_point_rejects_bad_intermediate_checksum() {
+        let mut v2 = entry_point2(0x1f);
+        v2[24] ^= 1;
+        assert!(SmbiosEntryPoint::parse(&v2).is_none());
+    }
+
+    #[test]
+    fn walks_structures_in_order() {
+        let table = sample_table();
+        let types: Vec<u8> = Structures::new(&table).map(|s| s.structure_type()).collect();
+        assert_eq!(types, [0, 1, 0xc0, 4, 17, 17, END_OF_TABLE]);
+    }
+
+    #[test]
+    fn structure_without_strings() {
+        let table = sample_table();
+        let unknown = nth(&table, 2);
+        assert_eq!(unknown.strings().count(), 0);
+        assert_eq!(unknown.formatted(), &[0xc0, 6, 2, 0, 1, 2]);
+    }
+
+    #[test]
+    fn strings_count_from_1() {
+        let table = sample_table();
+        let bios = nth(&table, 0);
+        assert_eq!(bios.string(0), None);
+        assert_eq!(bios.string(3), Some("01/02/2024"));
+        assert_eq!(bios.string(4), None);
+    }
+
+    #[test]
+    fn stops_at_truncated_structure() {
+        let mut table = sample_table();
+        // cuts the system information string set short
+        table.truncate(60);
+        assert_eq!(Structures::new(&table).count(), 1);
+    }
+
+    #[test]
+    fn typed_view_checks_type() {
+        let table = sample_table();
+        assert!(SystemInformation::new(nth(&table, 0)).is_none());
+    }
+
+    #[test]
+    fn bios_information() {
+        let table = sample_table();
+        let bios = BiosInformation::new(nth(&table, 0)).unwrap();
+        assert_eq!(bios.vendor(), Some("Vendor"));
+        assert_eq!(bios.release_date(), Some("01/02/2024"));
+        assert_eq!(bios.rom_size(), 16 << 16);
+        assert_eq!(bios.bios_release(), Some((1, 9)));
+        assert_eq!(bios.ec_release(), None);
+    }
+
+    #[test]
+    fn system_information() {
+        let table = sample_table();
+        let system = SystemInformation::new(nth(&table, 1)).unwrap();
+        assert_eq!(system.product_name(), Some("Box"));
+        assert_eq!(system.version(), None);
+        assert_eq!(system.serial_number(), Some("SN123"));
+        assert_eq!(alloc::format!("{}", system.uuid().unwrap()),
+                   "00112233-4455-6677-8899-aabbccddeeff");
+    }
+
+    #[test]
+    fn processor_information() {
+        let table = sample_table();
+        let cpu = ProcessorInformation::new(nth(&table, 3)).unwrap();
+        assert_eq!(cpu.socket_designation(), Some("CPU0"));
+        assert_eq!(cpu.family(), 0x0107);
+        assert_eq!(cpu.current_speed(), 3000);
+        assert!(cpu.is_socket_populated());
+        assert_eq!(cpu.core_count(), Some(288));
+        assert_eq!(cpu.cores_enabled(), Some(8));
+    }
+
+    #[test]
+    fn memory_device() {
+        let table = sample_table();
+        let dimm = MemoryDevice::new(nth(&table, 4)).unwrap();
+        assert_eq!(dimm.device_locator(), Some("DIMM_A1"));
+        assert_eq!(dimm.size(), Some(64 << 30));
+        assert_eq!(dimm.memory_type(), 0x22);
+    }
+
+    #[test]
+    fn empty_memory_slot() {
+        let table = sample_table();
+        let empty = MemoryDevice::new(nth(&table, 5)).unwrap();
+        assert!(!empty.is_installed());
+        assert_eq!(empty.size(), None);
+        assert_eq!(empty.configured_speed(), None);
+    }
+}
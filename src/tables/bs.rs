@@ -2,6 +2,9 @@ use core::ptr::NonNull;
 
 use super::hdr::*;
 use crate::types::*;
+use crate::proto::device_path::EfiDevicePathProtocol;
+use crate::efi_try;
+use crate::memory::{MemoryMap, PageAllocType, PageAllocation};
 
 use crate::safeptr::ThreadSafePtr;
 
@@ -17,7 +20,7 @@ pub struct EfiBootServices {
 
     pub restore_tpl: unsafe extern "efiapi" fn(
         old_tpl: EfiTpl,
-    ) -> VOID,
+    ),
 
     // memory services
     
@@ -29,7 +32,7 @@ pub struct EfiBootServices {
     ) -> EfiStatus,
 
     pub free_pages: unsafe extern "efiapi" fn(
-        memory: *mut EfiPhysicalAddress,
+        memory: EfiPhysicalAddress,
         pages: UINTN,
     ) -> EfiStatus,
 
@@ -50,43 +53,1046 @@ pub struct EfiBootServices {
     pub free_pool: unsafe extern "efiapi" fn(
         buffer: *mut VOID,
     ) -> EfiStatus,
+
+    // event & timer services
+
+    pub create_event: unsafe extern "efiapi" fn(
+        event_type: UINT32,
+        notify_tpl: EfiTpl,
+        notify_function: Option<EfiEventNotify>,
+        notify_context: *mut VOID,
+        event: *mut EfiEvent,
+    ) -> EfiStatus,
+
+    pub set_timer: unsafe extern "efiapi" fn(
+        event: EfiEvent,
+        timer_type: EfiTimerDelay,
+        trigger_time: UINT64,
+    ) -> EfiStatus,
+
+    pub wait_for_event: unsafe extern "efiapi" fn(
+        number_of_events: UINTN,
+        event: *mut EfiEvent,
+        index: *mut UINTN,
+    ) -> EfiStatus,
+
+    pub signal_event: unsafe extern "efiapi" fn(
+        event: EfiEvent,
+    ) -> EfiStatus,
+
+    pub close_event: unsafe extern "efiapi" fn(
+        event: EfiEvent,
+    ) -> EfiStatus,
+
+    pub check_event: unsafe extern "efiapi" fn(
+        event: EfiEvent,
+    ) -> EfiStatus,
+
+    // protocol handler services
+
+    pub install_protocol_interface: unsafe extern "efiapi" fn(
+        handle: *mut EfiHandle,
+        protocol: *mut EfiGuid,
+        interface_type: EfiInterfaceType,
+        interface: *mut VOID,
+    ) -> EfiStatus,
+
+    pub reinstall_protocol_interface: unsafe extern "efiapi" fn(
+        handle: EfiHandle,
+        protocol: *mut EfiGuid,
+        old_interface: *mut VOID,
+        new_interface: *mut VOID,
+    ) -> EfiStatus,
+
+    pub uninstall_protocol_interface: unsafe extern "efiapi" fn(
+        handle: EfiHandle,
+        protocol: *mut EfiGuid,
+        interface: *mut VOID,
+    ) -> EfiStatus,
+
+    pub handle_protocol: unsafe extern "efiapi" fn(
+        handle: EfiHandle,
+        protocol: *mut EfiGuid,
+        interface: *mut *mut VOID,
+    ) -> EfiStatus,
+
+    pub reserved: *mut VOID,
+
+    pub register_protocol_notify: unsafe extern "efiapi" fn(
+        protocol: *mut EfiGuid,
+        event: EfiEvent,
+        registration: *mut *mut VOID,
+    ) -> EfiStatus,
+
+    pub locate_handle: unsafe extern "efiapi" fn(
+        search_type: EfiLocateSearchType,
+        protocol: *mut EfiGuid,
+        search_key: *mut VOID,
+        buffer_size: *mut UINTN,
+        buffer: *mut EfiHandle,
+    ) -> EfiStatus,
+
+    pub locate_device_path: unsafe extern "efiapi" fn(
+        protocol: *mut EfiGuid,
+        device_path: *mut *mut EfiDevicePathProtocol,
+        device: *mut EfiHandle,
+    ) -> EfiStatus,
+
+    pub install_configuration_table: unsafe extern "efiapi" fn(
+        guid: *mut EfiGuid,
+        table: *mut VOID,
+    ) -> EfiStatus,
+
+    // image services
+
+    pub load_image: unsafe extern "efiapi" fn(
+        boot_policy: BOOLEAN,
+        parent_image_handle: EfiHandle,
+        device_path: *mut EfiDevicePathProtocol,
+        source_buffer: *mut VOID,
+        source_size: UINTN,
+        image_handle: *mut EfiHandle,
+    ) -> EfiStatus,
+
+    pub start_image: unsafe extern "efiapi" fn(
+        image_handle: EfiHandle,
+        exit_data_size: *mut UINTN,
+        exit_data: *mut *mut CHAR16,
+    ) -> EfiStatus,
+
+    pub exit: unsafe extern "efiapi" fn(
+        image_handle: EfiHandle,
+        exit_status: EfiStatus,
+        exit_data_size: UINTN,
+        exit_data: *mut CHAR16,
+    ) -> EfiStatus,
+
+    pub unload_image: unsafe extern "efiapi" fn(
+        image_handle: EfiHandle,
+    ) -> EfiStatus,
+
+    pub exit_boot_services: unsafe extern "efiapi" fn(
+        image_handle: EfiHandle,
+        map_key: UINTN,
+    ) -> EfiStatus,
+
+    // miscellaneous services
+
+    pub get_next_monotonic_count: unsafe extern "efiapi" fn(
+        count: *mut UINT64,
+    ) -> EfiStatus,
+
+    pub stall: unsafe extern "efiapi" fn(
+        microseconds: UINTN,
+    ) -> EfiStatus,
+
+    pub set_watchdog_timer: unsafe extern "efiapi" fn(
+        timeout: UINTN,
+        watchdog_code: UINT64,
+        data_size: UINTN,
+        watchdog_data: *mut CHAR16,
+    ) -> EfiStatus,
+
+    // driver support services
+
+    pub connect_controller: unsafe extern "efiapi" fn(
+        controller_handle: EfiHandle,
+        driver_image_handle: *mut EfiHandle,
+        remaining_device_path: *mut EfiDevicePathProtocol,
+        recursive: BOOLEAN,
+    ) -> EfiStatus,
+
+    pub disconnect_controller: unsafe extern "efiapi" fn(
+        controller_handle: EfiHandle,
+        driver_image_handle: EfiHandle,
+        child_handle: EfiHandle,
+    ) -> EfiStatus,
+
+    // open and close protocol services
+
+    pub open_protocol: unsafe extern "efiapi" fn(
+        handle: EfiHandle,
+        protocol: *mut EfiGuid,
+        interface: *mut *mut VOID,
+        agent_handle: EfiHandle,
+        controller_handle: EfiHandle,
+        attributes: UINT32,
+    ) -> EfiStatus,
+
+    pub close_protocol: unsafe extern "efiapi" fn(
+        handle: EfiHandle,
+        protocol: *mut EfiGuid,
+        agent_handle: EfiHandle,
+        controller_handle: EfiHandle,
+    ) -> EfiStatus,
+
+    pub open_protocol_information: unsafe extern "efiapi" fn(
+        handle: EfiHandle,
+        protocol: *mut EfiGuid,
+        entry_buffer: *mut *mut EfiOpenProtocolInformationEntry,
+        entry_count: *mut UINTN,
+    ) -> EfiStatus,
+
+    // library services
+
+    pub protocols_per_handle: unsafe extern "efiapi" fn(
+        handle: EfiHandle,
+        protocol_buffer: *mut *mut *mut EfiGuid,
+        protocol_buffer_count: *mut UINTN,
+    ) -> EfiStatus,
+
+    pub locate_handle_buffer: unsafe extern "efiapi" fn(
+        search_type: EfiLocateSearchType,
+        protocol: *mut EfiGuid,
+        search_key: *mut VOID,
+        no_handles: *mut UINTN,
+        buffer: *mut *mut EfiHandle,
+    ) -> EfiStatus,
+
+    pub locate_protocol: unsafe extern "efiapi" fn(
+        protocol: *mut EfiGuid,
+        registration: *mut VOID,
+        interface: *mut *mut VOID,
+    ) -> EfiStatus,
+
+    pub install_multiple_protocol_interfaces: unsafe extern "efiapi" fn(
+        handle: *mut EfiHandle,
+        ...
+    ) -> EfiStatus,
+
+    pub uninstall_multiple_protocol_interfaces: unsafe extern "efiapi" fn(
+        handle: EfiHandle,
+        ...
+    ) -> EfiStatus,
+
+    // 32-bit crc services
+
+    pub calculate_crc32: unsafe extern "efiapi" fn(
+        data: *mut VOID,
+        data_size: UINTN,
+        crc32: *mut UINT32,
+    ) -> EfiStatus,
+
+    // miscellaneous services
+
+    pub copy_mem: unsafe extern "efiapi" fn(
+        destination: *mut VOID,
+        source: *mut VOID,
+        length: UINTN,
+    ),
+
+    pub set_mem: unsafe extern "efiapi" fn(
+        buffer: *mut VOID,
+        size: UINTN,
+        value: UINT8,
+    ),
+
+    pub create_event_ex: unsafe extern "efiapi" fn(
+        event_type: UINT32,
+        notify_tpl: EfiTpl,
+        notify_function: Option<EfiEventNotify>,
+        notify_context: *const VOID,
+        event_group: *const EfiGuid,
+        event: *mut EfiEvent,
+    ) -> EfiStatus,
 }
 
+// layout checks against the uefi 2.10 specification, section 4.4
+
+#[cfg(target_pointer_width = "64")]
+const _: () = {
+    use core::mem::{offset_of, size_of};
+
+    assert!(size_of::<EfiTableHeader>() == 0x18);
+    assert!(offset_of!(EfiBootServices, raise_tpl) == 0x18);
+    assert!(offset_of!(EfiBootServices, restore_tpl) == 0x20);
+    assert!(offset_of!(EfiBootServices, allocate_pages) == 0x28);
+    assert!(offset_of!(EfiBootServices, free_pages) == 0x30);
+    assert!(offset_of!(EfiBootServices, get_memory_map) == 0x38);
+    assert!(offset_of!(EfiBootServices, allocate_pool) == 0x40);
+    assert!(offset_of!(EfiBootServices, free_pool) == 0x48);
+    assert!(offset_of!(EfiBootServices, create_event) == 0x50);
+    assert!(offset_of!(EfiBootServices, set_timer) == 0x58);
+    assert!(offset_of!(EfiBootServices, wait_for_event) == 0x60);
+    assert!(offset_of!(EfiBootServices, signal_event) == 0x68);
+    assert!(offset_of!(EfiBootServices, close_event) == 0x70);
+    assert!(offset_of!(EfiBootServices, check_event) == 0x78);
+    assert!(offset_of!(EfiBootServices, install_protocol_interface) == 0x80);
+    assert!(offset_of!(EfiBootServices, reinstall_protocol_interface) == 0x88);
+    assert!(offset_of!(EfiBootServices, uninstall_protocol_interface) == 0x90);
+    assert!(offset_of!(EfiBootServices, handle_protocol) == 0x98);
+    assert!(offset_of!(EfiBootServices, reserved) == 0xa0);
+    assert!(offset_of!(EfiBootServices, register_protocol_notify) == 0xa8);
+    assert!(offset_of!(EfiBootServices, locate_handle) == 0xb0);
+    assert!(offset_of!(EfiBootServices, locate_device_path) == 0xb8);
+    assert!(offset_of!(EfiBootServices, install_configuration_table) == 0xc0);
+    assert!(offset_of!(EfiBootServices, load_image) == 0xc8);
+    assert!(offset_of!(EfiBootServices, start_image) == 0xd0);
+    assert!(offset_of!(EfiBootServices, exit) == 0xd8);
+    assert!(offset_of!(EfiBootServices, unload_image) == 0xe0);
+    assert!(offset_of!(EfiBootServices, exit_boot_services) == 0xe8);
+    assert!(offset_of!(EfiBootServices, get_next_monotonic_count) == 0xf0);
+    assert!(offset_of!(EfiBootServices, stall) == 0xf8);
+    assert!(offset_of!(EfiBootServices, set_watchdog_timer) == 0x100);
+    assert!(offset_of!(EfiBootServices, connect_controller) == 0x108);
+    assert!(offset_of!(EfiBootServices, disconnect_controller) == 0x110);
+    assert!(offset_of!(EfiBootServices, open_protocol) == 0x118);
+    assert!(offset_of!(EfiBootServices, close_protocol) == 0x120);
+    assert!(offset_of!(EfiBootServices, open_protocol_information) == 0x128);
+    assert!(offset_of!(EfiBootServices, protocols_per_handle) == 0x130);
+    assert!(offset_of!(EfiBootServices, locate_handle_buffer) == 0x138);
+    assert!(offset_of!(EfiBootServices, locate_protocol) == 0x140);
+    assert!(offset_of!(EfiBootServices, install_multiple_protocol_interfaces) == 0x148);
+    assert!(offset_of!(EfiBootServices, uninstall_multiple_protocol_interfaces) == 0x150);
+    assert!(offset_of!(EfiBootServices, calculate_crc32) == 0x158);
+    assert!(offset_of!(EfiBootServices, copy_mem) == 0x160);
+    assert!(offset_of!(EfiBootServices, set_mem) == 0x168);
+    assert!(offset_of!(EfiBootServices, create_event_ex) == 0x170);
+    assert!(size_of::<EfiBootServices>() == 0x178);
+};
+
 pub struct BootServices {
     pub services: ThreadSafePtr<EfiBootServices>,
 }
 
 impl BootServices {
+    /// Returns the previous TPL.
+    ///
+    /// # Safety
+    ///
+    /// `new_tpl` must not be below the current TPL, and the previous level
+    /// must be restored with `restore_tpl`. Prefer
+    /// [`TplGuard`](crate::tpl::TplGuard).
+    pub unsafe fn raise_tpl(&self, new_tpl: EfiTpl) -> EfiTpl {
+        unsafe { ((*self.services.as_ptr()).raise_tpl)(new_tpl) }
+    }
+
+    /// # Safety
+    ///
+    /// `old_tpl` must be a level returned by `raise_tpl` and not above the
+    /// current TPL.
+    pub unsafe fn restore_tpl(&self, old_tpl: EfiTpl) {
+        unsafe { ((*self.services.as_ptr()).restore_tpl)(old_tpl) }
+    }
+
+    /// Allocates `pages` pages; the returned guard frees them on drop. The
+    /// contents are whatever the memory last held.
+    pub fn allocate_pages(&self,
+                          alloc_type: PageAllocType,
+                          memory_type: EfiMemoryType,
+                          pages: UINTN
+    ) -> Result<PageAllocation, EfiStatus> {
+        let (alloc_type, mut memory) = match alloc_type {
+            PageAllocType::AnyPages => (EfiAllocateType::AllocateAnyPages, 0),
+            PageAllocType::MaxAddress(max) => (EfiAllocateType::AllocateMaxAddress, max),
+            PageAllocType::Address(addr) => (EfiAllocateType::AllocateAddress, addr),
+        };
+        let status = unsafe {
+            ((*self.services.as_ptr()).allocate_pages)(
+                alloc_type,
+                memory_type,
+                pages,
+                &mut memory,
+            )
+        };
+        efi_try!(status)?;
+
+        // SAFETY: the firmware just handed us ownership of this range
+        Ok(unsafe { PageAllocation::from_raw(memory, pages) })
+    }
+
+    /// Like [`BootServices::allocate_pages`], with the pages zeroed.
+    pub fn allocate_pages_zeroed(&self,
+                                 alloc_type: PageAllocType,
+                                 memory_type: EfiMemoryType,
+                                 pages: UINTN
+    ) -> Result<PageAllocation, EfiStatus> {
+        let mut allocation = self.allocate_pages(alloc_type, memory_type, pages)?;
+        unsafe { allocation.as_mut_ptr().write_bytes(0, allocation.size()) };
+        Ok(allocation)
+    }
+
+    /// # Safety
+    ///
+    /// The range must have been allocated with `allocate_pages` and must not
+    /// be referenced afterwards.
+    pub unsafe fn free_pages(&self,
+                             memory: EfiPhysicalAddress,
+                             pages: UINTN
+    ) -> Result<(), EfiStatus> {
+        let status = unsafe {
+            ((*self.services.as_ptr()).free_pages)(memory, pages)
+        };
+        efi_try!(status)
+    }
+
+    /// Fetches the current memory map, growing the buffer until it fits.
+    pub fn memory_map(&self) -> Result<MemoryMap, EfiStatus> {
+        let mut map = MemoryMap::new();
+        loop {
+            match map.fetch(self) {
+                Err(EfiStatus::BUFFER_TOO_SMALL) => map.grow(),
+                result => return result.map(|_| map),
+            }
+        }
+    }
+
     pub fn allocate_pool(&self, 
                          pool_type: EfiMemoryType,
                          size: UINTN
     ) -> Result<NonNull<VOID>, EfiStatus> {
         let mut buffer: *mut VOID = core::ptr::null_mut();
-
-        unsafe {
-            let status = ((*self.services.as_ptr()).allocate_pool)(
+        let status = unsafe {
+            ((*self.services.as_ptr()).allocate_pool)(
                 pool_type,
                 size,
                 &mut buffer
-            );
-            if status.is_success() {
-                NonNull::new(buffer).ok_or(EfiStatus::EfiBufferTooSmall)
-            } else {
-                Err(status)
-            }
-        }
+            )
+        };
+        efi_try!(status)?;
+        NonNull::new(buffer).ok_or(EfiStatus::BUFFER_TOO_SMALL)
     } 
 
     pub fn free_pool(&self, buffer: NonNull<VOID>) -> Result<(), EfiStatus> {
-        unsafe {
-            let bs = self.services.as_ptr();
-            
-            let status = ((*bs).free_pool)(buffer.as_ptr());
-            if status.is_success() {
-                Ok(())
-            } else {
-                Err(status)
+        let status = unsafe {
+            ((*self.services.as_ptr()).free_pool)(buffer.as_ptr())
+        };
+        efi_try!(status)
+    }
+
+    /// # Safety
+    ///
+    /// `notify_function` is invoked by firmware with `notify_context`; the
+    /// context must stay valid for as long as the event exists.
+    pub unsafe fn create_event(&self,
+                               event_type: UINT32,
+                               notify_tpl: EfiTpl,
+                               notify_function: Option<EfiEventNotify>,
+                               notify_context: *mut VOID
+    ) -> Result<EfiEvent, EfiStatus> {
+        let mut event: EfiEvent = core::ptr::null_mut();
+        let status = unsafe {
+            ((*self.services.as_ptr()).create_event)(
+                event_type,
+                notify_tpl,
+                notify_function,
+                notify_context,
+                &mut event,
+            )
+        };
+        efi_try!(status).map(|_| event)
+    }
+
+    /// # Safety
+    ///
+    /// Same requirements as [`BootServices::create_event`].
+    pub unsafe fn create_event_ex(&self,
+                                  event_type: UINT32,
+                                  notify_tpl: EfiTpl,
+                                  notify_function: Option<EfiEventNotify>,
+                                  notify_context: *const VOID,
+                                  event_group: Option<&EfiGuid>
+    ) -> Result<EfiEvent, EfiStatus> {
+        let mut event: EfiEvent = core::ptr::null_mut();
+        let group = event_group.map_or(core::ptr::null(), |g| g as *const _);
+        let status = unsafe {
+            ((*self.services.as_ptr()).create_event_ex)(
+                event_type,
+                notify_tpl,
+                notify_function,
+                notify_context,
+                group,
+                &mut event,
+            )
+        };
+        efi_try!(status).map(|_| event)
+    }
+
+    /// # Safety
+    ///
+    /// `event` must be a live event.
+    pub unsafe fn set_timer(&self,
+                            event: EfiEvent,
+                            timer_type: EfiTimerDelay,
+                            trigger_time: UINT64
+    ) -> Result<(), EfiStatus> {
+        let status = unsafe {
+            ((*self.services.as_ptr()).set_timer)(
+                event,
+                timer_type,
+                trigger_time,
+            )
+        };
+        efi_try!(status)
+    }
+
+    pub fn wait_for_event(&self,
+                          events: &mut [EfiEvent]
+    ) -> Result<UINTN, EfiStatus> {
+        let mut index: UINTN = 0;
+        let status = unsafe {
+            ((*self.services.as_ptr()).wait_for_event)(
+                events.len(),
+                events.as_mut_ptr(),
+                &mut index,
+            )
+        };
+        efi_try!(status).map(|_| index)
+    }
+
+    /// # Safety
+    ///
+    /// `event` must be a live event.
+    pub unsafe fn signal_event(&self, event: EfiEvent) -> Result<(), EfiStatus> {
+        let status = unsafe {
+            ((*self.services.as_ptr()).signal_event)(event)
+        };
+        efi_try!(status)
+    }
+
+    /// # Safety
+    ///
+    /// `event` must not be used again after it has been closed.
+    pub unsafe fn close_event(&self, event: EfiEvent) -> Result<(), EfiStatus> {
+        let status = unsafe {
+            ((*self.services.as_ptr()).close_event)(event)
+        };
+        efi_try!(status)
+    }
+
+    /// Returns whether `event` was signaled, clearing the signal.
+    ///
+    /// # Safety
+    ///
+    /// `event` must be a live event.
+    pub unsafe fn check_event(&self, event: EfiEvent) -> Result<bool, EfiStatus> {
+        let status = unsafe {
+            ((*self.services.as_ptr()).check_event)(event)
+        };
+        match status {
+            EfiStatus::NOT_READY => Ok(false),
+            status => efi_try!(status).map(|_| true),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `interface` must point to a structure matching `protocol` and must
+    /// outlive its installation.
+    pub unsafe fn install_protocol_interface(&self,
+                                             handle: Option<EfiHandle>,
+                                             protocol: &EfiGuid,
+                                             interface: *mut VOID
+    ) -> Result<EfiHandle, EfiStatus> {
+        let mut handle = handle.unwrap_or(core::ptr::null_mut());
+        let mut guid = *protocol;
+        let status = unsafe {
+            ((*self.services.as_ptr()).install_protocol_interface)(
+                &mut handle,
+                &mut guid,
+                EfiInterfaceType::EfiNativeInterface,
+                interface,
+            )
+        };
+        efi_try!(status).map(|_| handle)
+    }
+
+    /// # Safety
+    ///
+    /// `old_interface` must be the currently installed interface and
+    /// `new_interface` must satisfy the requirements of
+    /// [`BootServices::install_protocol_interface`].
+    pub unsafe fn reinstall_protocol_interface(&self,
+                                               handle: EfiHandle,
+                                               protocol: &EfiGuid,
+                                               old_interface: *mut VOID,
+                                               new_interface: *mut VOID
+    ) -> Result<(), EfiStatus> {
+        let mut guid = *protocol;
+        let status = unsafe {
+            ((*self.services.as_ptr()).reinstall_protocol_interface)(
+                handle,
+                &mut guid,
+                old_interface,
+                new_interface,
+            )
+        };
+        efi_try!(status)
+    }
+
+    /// # Safety
+    ///
+    /// No other image may still be using `interface`.
+    pub unsafe fn uninstall_protocol_interface(&self,
+                                               handle: EfiHandle,
+                                               protocol: &EfiGuid,
+                                               interface: *mut VOID
+    ) -> Result<(), EfiStatus> {
+        let mut guid = *protocol;
+        let status = unsafe {
+            ((*self.services.as_ptr()).uninstall_protocol_interface)(
+                handle,
+                &mut guid,
+                interface,
+            )
+        };
+        efi_try!(status)
+    }
+
+    /// # Safety
+    ///
+    /// `handle` must be a live handle.
+    pub unsafe fn handle_protocol_raw(&self,
+                                      handle: EfiHandle,
+                                      protocol: &EfiGuid
+    ) -> Result<NonNull<VOID>, EfiStatus> {
+        let mut guid = *protocol;
+        let mut interface: *mut VOID = core::ptr::null_mut();
+        let status = unsafe {
+            ((*self.services.as_ptr()).handle_protocol)(
+                handle,
+                &mut guid,
+                &mut interface,
+            )
+        };
+        efi_try!(status)?;
+        NonNull::new(interface).ok_or(EfiStatus::UNSUPPORTED)
+    }
+
+    /// # Safety
+    ///
+    /// `event` must be a live event, and stay open for as long as the
+    /// returned registration is used.
+    pub unsafe fn register_protocol_notify(&self,
+                                           protocol: &EfiGuid,
+                                           event: EfiEvent
+    ) -> Result<*mut VOID, EfiStatus> {
+        let mut guid = *protocol;
+        let mut registration: *mut VOID = core::ptr::null_mut();
+        let status = unsafe {
+            ((*self.services.as_ptr()).register_protocol_notify)(
+                &mut guid,
+                event,
+                &mut registration,
+            )
+        };
+        efi_try!(status).map(|_| registration)
+    }
+
+    /// Returns the number of handles found; on `EfiStatus::BUFFER_TOO_SMALL`
+    /// the required buffer length is lost, so size `buffer` generously.
+    ///
+    /// # Safety
+    ///
+    /// For `ByRegisterNotify`, `search_key` must be a registration returned
+    /// by [`BootServices::register_protocol_notify`] whose event is still
+    /// open; otherwise it is ignored.
+    pub unsafe fn locate_handle(&self,
+                                search_type: EfiLocateSearchType,
+                                protocol: Option<&EfiGuid>,
+                                search_key: *mut VOID,
+                                buffer: &mut [EfiHandle]
+    ) -> Result<UINTN, EfiStatus> {
+        let mut guid = protocol.copied();
+        let guid_ptr = guid.as_mut().map_or(core::ptr::null_mut(), |g| g as *mut _);
+        let mut size = core::mem::size_of_val(buffer);
+        let status = unsafe {
+            ((*self.services.as_ptr()).locate_handle)(
+                search_type,
+                guid_ptr,
+                search_key,
+                &mut size,
+                buffer.as_mut_ptr(),
+            )
+        };
+        efi_try!(status).map(|_| size / core::mem::size_of::<EfiHandle>())
+    }
+
+    /// # Safety
+    ///
+    /// `device_path` must point to a valid, terminated device path.
+    pub unsafe fn locate_device_path(&self,
+                                     protocol: &EfiGuid,
+                                     device_path: &mut *mut EfiDevicePathProtocol
+    ) -> Result<EfiHandle, EfiStatus> {
+        let mut guid = *protocol;
+        let mut device: EfiHandle = core::ptr::null_mut();
+        let status = unsafe {
+            ((*self.services.as_ptr()).locate_device_path)(
+                &mut guid,
+                device_path,
+                &mut device,
+            )
+        };
+        efi_try!(status).map(|_| device)
+    }
+
+    /// # Safety
+    ///
+    /// `table` must stay valid for as long as it is installed, and must be
+    /// allocated as runtime memory if it is used after boot services exit.
+    pub unsafe fn install_configuration_table(&self,
+                                              guid: &EfiGuid,
+                                              table: *mut VOID
+    ) -> Result<(), EfiStatus> {
+        let mut guid = *guid;
+        let status = unsafe {
+            ((*self.services.as_ptr()).install_configuration_table)(
+                &mut guid,
+                table,
+            )
+        };
+        efi_try!(status)
+    }
+
+    /// An image rejected by the security policy is unloaded before
+    /// `EfiStatus::SECURITY_VIOLATION` is returned.
+    ///
+    /// # Safety
+    ///
+    /// `device_path` must be null or a valid device path, and
+    /// `source_buffer` must be null or point to `source_size` readable bytes.
+    pub unsafe fn load_image_raw(&self,
+                                 boot_policy: BOOLEAN,
+                                 parent_image_handle: EfiHandle,
+                                 device_path: *mut EfiDevicePathProtocol,
+                                 source_buffer: *mut VOID,
+                                 source_size: UINTN
+    ) -> Result<EfiHandle, EfiStatus> {
+        let mut image: EfiHandle = core::ptr::null_mut();
+        let status = unsafe {
+            ((*self.services.as_ptr()).load_image)(
+                boot_policy,
+                parent_image_handle,
+                device_path,
+                source_buffer,
+                source_size,
+                &mut image,
+            )
+        };
+        // the image is loaded even though it may not be started
+        if status == EfiStatus::SECURITY_VIOLATION && !image.is_null() {
+            let _ = unsafe { self.unload_image(image) };
+        }
+        efi_try!(status).map(|_| image)
+    }
+
+    /// # Safety
+    ///
+    /// `image_handle` must be a loaded image other than the running one, and
+    /// nothing it provided, such as protocol interfaces, may be used afterwards.
+    pub unsafe fn unload_image(&self, image_handle: EfiHandle) -> Result<(), EfiStatus> {
+        let status = unsafe {
+            ((*self.services.as_ptr()).unload_image)(image_handle)
+        };
+        efi_try!(status)
+    }
+
+    /// Terminates boot services and returns the final memory map.
+    ///
+    /// On success `BOOT_SERVICES.get()` and the boot-time accessors on
+    /// `SystemTable` return `None`, the boot-time fields of the system table
+    /// are cleared and the global allocator stops working.
+    ///
+    /// # Safety
+    ///
+    /// `image_handle` must be the running image. On success every
+    /// `&BootServices`, including `self`, and every protocol interface
+    /// obtained earlier point into firmware that is gone and must not be
+    /// used again; only access through the globals is checked.
+    pub unsafe fn exit_boot_services(&self,
+                                     image_handle: EfiHandle
+    ) -> Result<MemoryMap, EfiStatus> {
+        // the map key goes stale whenever firmware touches the map between
+        // our get_memory_map and exit_boot_services; give it a few chances
+        const MAX_ATTEMPTS: usize = 8;
+
+        let mut map = self.memory_map()?;
+        let mut result = Err(EfiStatus::INVALID_PARAMETER);
+        for _ in 0..MAX_ATTEMPTS {
+            let status = unsafe {
+                ((*self.services.as_ptr()).exit_boot_services)(
+                    image_handle,
+                    map.map_key(),
+                )
+            };
+            result = efi_try!(status);
+            if status != EfiStatus::INVALID_PARAMETER {
+                break;
+            }
+
+            // only memory allocation services may be used from here on
+            loop {
+                match map.fetch(self) {
+                    Err(EfiStatus::BUFFER_TOO_SMALL) => map.grow(),
+                    fetched => {
+                        fetched?;
+                        break;
+                    }
+                }
             }
         }
+        result?;
+
+        crate::poison_boot_services();
+        Ok(map)
+    }
+
+    pub fn get_next_monotonic_count(&self) -> Result<UINT64, EfiStatus> {
+        let mut count: UINT64 = 0;
+        let status = unsafe {
+            ((*self.services.as_ptr()).get_next_monotonic_count)(&mut count)
+        };
+        efi_try!(status).map(|_| count)
+    }
+
+    pub fn stall(&self, microseconds: UINTN) -> Result<(), EfiStatus> {
+        let status = unsafe {
+            ((*self.services.as_ptr()).stall)(microseconds)
+        };
+        efi_try!(status)
+    }
+
+    /// A `timeout` of zero disables the watchdog.
+    pub fn set_watchdog_timer(&self,
+                              timeout: UINTN,
+                              watchdog_code: UINT64,
+                              watchdog_data: Option<&mut [CHAR16]>
+    ) -> Result<(), EfiStatus> {
+        let (size, data) = match watchdog_data {
+            Some(data) => (core::mem::size_of_val(data), data.as_mut_ptr()),
+            None => (0, core::ptr::null_mut()),
+        };
+        let status = unsafe {
+            ((*self.services.as_ptr()).set_watchdog_timer)(
+                timeout,
+                watchdog_code,
+                size,
+                data,
+            )
+        };
+        efi_try!(status)
+    }
+
+    /// # Safety
+    ///
+    /// `driver_image_handle` must be null or a null-terminated handle list,
+    /// and `remaining_device_path` must be null or a valid device path.
+    pub unsafe fn connect_controller_raw(&self,
+                                         controller_handle: EfiHandle,
+                                         driver_image_handle: *mut EfiHandle,
+                                         remaining_device_path: *mut EfiDevicePathProtocol,
+                                         recursive: bool
+    ) -> Result<(), EfiStatus> {
+        let status = unsafe {
+            ((*self.services.as_ptr()).connect_controller)(
+                controller_handle,
+                driver_image_handle,
+                remaining_device_path,
+                recursive as BOOLEAN,
+            )
+        };
+        efi_try!(status)
+    }
+
+    /// # Safety
+    ///
+    /// The handles must be live. Protocols the stopped drivers opened are
+    /// closed, so interfaces obtained through them must not be used afterwards.
+    pub unsafe fn disconnect_controller(&self,
+                                        controller_handle: EfiHandle,
+                                        driver_image_handle: Option<EfiHandle>,
+                                        child_handle: Option<EfiHandle>
+    ) -> Result<(), EfiStatus> {
+        let status = unsafe {
+            ((*self.services.as_ptr()).disconnect_controller)(
+                controller_handle,
+                driver_image_handle.unwrap_or(core::ptr::null_mut()),
+                child_handle.unwrap_or(core::ptr::null_mut()),
+            )
+        };
+        efi_try!(status)
+    }
+
+    /// # Safety
+    ///
+    /// `handle` and `agent_handle` must be live handles, and
+    /// `controller_handle` null or live.
+    pub unsafe fn open_protocol_raw(&self,
+                                    handle: EfiHandle,
+                                    protocol: &EfiGuid,
+                                    agent_handle: EfiHandle,
+                                    controller_handle: EfiHandle,
+                                    attributes: UINT32
+    ) -> Result<*mut VOID, EfiStatus> {
+        let mut guid = *protocol;
+        let mut interface: *mut VOID = core::ptr::null_mut();
+        let status = unsafe {
+            ((*self.services.as_ptr()).open_protocol)(
+                handle,
+                &mut guid,
+                &mut interface,
+                agent_handle,
+                controller_handle,
+                attributes,
+            )
+        };
+        efi_try!(status).map(|_| interface)
+    }
+
+    /// # Safety
+    ///
+    /// The handles must be live, and the interface opened with them must not
+    /// be used afterwards.
+    pub unsafe fn close_protocol_raw(&self,
+                                     handle: EfiHandle,
+                                     protocol: &EfiGuid,
+                                     agent_handle: EfiHandle,
+                                     controller_handle: EfiHandle
+    ) -> Result<(), EfiStatus> {
+        let mut guid = *protocol;
+        let status = unsafe {
+            ((*self.services.as_ptr()).close_protocol)(
+                handle,
+                &mut guid,
+                agent_handle,
+                controller_handle,
+            )
+        };
+        efi_try!(status)
+    }
+
+    /// Returns a pool allocation of `count` entries which the caller must
+    /// release with [`BootServices::free_pool`].
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a live handle.
+    pub unsafe fn open_protocol_information(&self,
+                                            handle: EfiHandle,
+                                            protocol: &EfiGuid
+    ) -> Result<(NonNull<EfiOpenProtocolInformationEntry>, UINTN), EfiStatus> {
+        let mut guid = *protocol;
+        let mut buffer: *mut EfiOpenProtocolInformationEntry = core::ptr::null_mut();
+        let mut count: UINTN = 0;
+        let status = unsafe {
+            ((*self.services.as_ptr()).open_protocol_information)(
+                handle,
+                &mut guid,
+                &mut buffer,
+                &mut count,
+            )
+        };
+        efi_try!(status)?;
+        NonNull::new(buffer).map(|b| (b, count)).ok_or(EfiStatus::UNSUPPORTED)
+    }
+
+    /// Returns a pool allocation of `count` GUID pointers which the caller
+    /// must release with [`BootServices::free_pool`].
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a live handle.
+    pub unsafe fn protocols_per_handle_raw(&self,
+                                           handle: EfiHandle
+    ) -> Result<(NonNull<*mut EfiGuid>, UINTN), EfiStatus> {
+        let mut buffer: *mut *mut EfiGuid = core::ptr::null_mut();
+        let mut count: UINTN = 0;
+        let status = unsafe {
+            ((*self.services.as_ptr()).protocols_per_handle)(
+                handle,
+                &mut buffer,
+                &mut count,
+            )
+        };
+        efi_try!(status)?;
+        NonNull::new(buffer).map(|b| (b, count)).ok_or(EfiStatus::UNSUPPORTED)
+    }
+
+    /// Returns a pool allocation of `count` handles which the caller must
+    /// release with [`BootServices::free_pool`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`BootServices::locate_handle`].
+    pub unsafe fn locate_handle_buffer(&self,
+                                       search_type: EfiLocateSearchType,
+                                       protocol: Option<&EfiGuid>,
+                                       search_key: *mut VOID
+    ) -> Result<(NonNull<EfiHandle>, UINTN), EfiStatus> {
+        let mut guid = protocol.copied();
+        let guid_ptr = guid.as_mut().map_or(core::ptr::null_mut(), |g| g as *mut _);
+        let mut buffer: *mut EfiHandle = core::ptr::null_mut();
+        let mut count: UINTN = 0;
+        let status = unsafe {
+            ((*self.services.as_ptr()).locate_handle_buffer)(
+                search_type,
+                guid_ptr,
+                search_key,
+                &mut count,
+                &mut buffer,
+            )
+        };
+        efi_try!(status)?;
+        NonNull::new(buffer).map(|b| (b, count)).ok_or(EfiStatus::UNSUPPORTED)
+    }
+
+    /// # Safety
+    ///
+    /// `registration` must be null or a registration returned by
+    /// [`BootServices::register_protocol_notify`] whose event is still open.
+    pub unsafe fn locate_protocol_raw(&self,
+                                      protocol: &EfiGuid,
+                                      registration: *mut VOID
+    ) -> Result<NonNull<VOID>, EfiStatus> {
+        let mut guid = *protocol;
+        let mut interface: *mut VOID = core::ptr::null_mut();
+        let status = unsafe {
+            ((*self.services.as_ptr()).locate_protocol)(
+                &mut guid,
+                registration,
+                &mut interface,
+            )
+        };
+        efi_try!(status)?;
+        NonNull::new(interface).ok_or(EfiStatus::UNSUPPORTED)
+    }
+
+    pub fn calculate_crc32(&self, data: &[u8]) -> Result<UINT32, EfiStatus> {
+        let mut crc: UINT32 = 0;
+        let status = unsafe {
+            ((*self.services.as_ptr()).calculate_crc32)(
+                data.as_ptr() as *mut VOID,
+                data.len(),
+                &mut crc,
+            )
+        };
+        efi_try!(status).map(|_| crc)
+    }
+
+    /// # Safety
+    ///
+    /// Both regions must be valid for `length` bytes; they may overlap.
+    pub unsafe fn copy_mem(&self,
+                           destination: *mut VOID,
+                           source: *const VOID,
+                           length: UINTN
+    ) {
+        unsafe {
+            ((*self.services.as_ptr()).copy_mem)(
+                destination,
+                source as *mut VOID,
+                length,
+            )
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `buffer` must be valid for writes of `size` bytes.
+    pub unsafe fn set_mem(&self, buffer: *mut VOID, size: UINTN, value: UINT8) {
+        unsafe {
+            ((*self.services.as_ptr()).set_mem)(buffer, size, value)
+        }
     }
 }
@@ -0,0 +1,108 @@
+//! Configuration tables published through the system table.
+
+use super::st::*;
+use crate::types::*;
+
+/// ACPI 1.0 RSDP.
+pub const ACPI_TABLE_GUID: EfiGuid = EfiGuid {
+    data1: 0xeb9d2d30,
+    data2: 0x2d88,
+    data3: 0x11d3,
+    data4: [0x9a,0x16,0x00,0x90,0x27,0x3f,0xc1,0x4d],
+};
+
+/// ACPI 2.0 and later RSDP.
+pub const EFI_ACPI_TABLE_GUID: EfiGuid = EfiGuid {
+    data1: 0x8868e871,
+    data2: 0xe4f1,
+    data3: 0x11d3,
+    data4: [0xbc,0x22,0x00,0x80,0xc7,0x3c,0x88,0x81],
+};
+
+/// SMBIOS 2.x entry point.
+pub const SMBIOS_TABLE_GUID: EfiGuid = EfiGuid {
+    data1: 0xeb9d2d31,
+    data2: 0x2d88,
+    data3: 0x11d3,
+    data4: [0x9a,0x16,0x00,0x90,0x27,0x3f,0xc1,0x4d],
+};
+
+/// SMBIOS 3.x 64-bit entry point.
+pub const SMBIOS3_TABLE_GUID: EfiGuid = EfiGuid {
+    data1: 0xf2fd1544,
+    data2: 0x9794,
+    data3: 0x4a2c,
+    data4: [0x99,0x2e,0xe5,0xbb,0xcf,0x20,0xe3,0x94],
+};
+
+/// Flattened device tree blob.
+pub const EFI_DTB_TABLE_GUID: EfiGuid = EfiGuid {
+    data1: 0xb1b621d5,
+    data2: 0xf19c,
+    data3: 0x41a5,
+    data4: [0x83,0x0b,0xd9,0x15,0x2c,0x69,0xaa,0xe0],
+};
+
+pub const EFI_MEMORY_ATTRIBUTES_TABLE_GUID: EfiGuid = EfiGuid {
+    data1: 0xdcfa911d,
+    data2: 0x26eb,
+    data3: 0x469f,
+    data4: [0xa2,0x20,0x38,0xb7,0xdc,0x46,0x12,0x20],
+};
+
+/// EFI System Resource Table (ESRT).
+pub const EFI_SYSTEM_RESOURCE_TABLE_GUID: EfiGuid = EfiGuid {
+    data1: 0xb122a263,
+    data2: 0x3661,
+    data3: 0x4f68,
+    data4: [0x99,0x29,0x78,0xf8,0xb0,0xd6,0x21,0x80],
+};
+
+pub const EFI_RT_PROPERTIES_TABLE_GUID: EfiGuid = EfiGuid {
+    data1: 0xeb66918a,
+    data2: 0x7eef,
+    data3: 0x402a,
+    data4: [0x84,0x2e,0x93,0x1d,0x21,0xc3,0x8a,0xe9],
+};
+
+pub const EFI_DEBUG_IMAGE_INFO_TABLE_GUID: EfiGuid = EfiGuid {
+    data1: 0x49152e77,
+    data2: 0x1ada,
+    data3: 0x4764,
+    data4: [0xb7,0xa2,0x7a,0xfe,0xfe,0xd9,0x5e,0x8b],
+};
+
+/// PI hand-off block list.
+pub const HOB_LIST_GUID: EfiGuid = EfiGuid {
+    data1: 0x7739f24c,
+    data2: 0x93d7,
+    data3: 0x11d4,
+    data4: [0x9a,0x3a,0x00,0x90,0x27,0x3f,0xc1,0x4d],
+};
+
+impl SystemTable {
+    /// The raw configuration table entries.
+    pub fn config_table_entries(&self) -> &[EfiConfigurationTable] {
+        let table = unsafe { &*self.table.as_ptr() };
+        if table.configuration_table.is_null() {
+            return &[];
+        }
+        unsafe {
+            core::slice::from_raw_parts(table.configuration_table, table.number_of_table_entries)
+        }
+    }
+
+    /// Vendor GUID and table pointer of every configuration table.
+    pub fn config_tables(&self) -> impl Iterator<Item = (EfiGuid, *const VOID)> + '_ {
+        self.config_table_entries()
+            .iter()
+            .map(|entry| (entry.vendor_guid, entry.vendor_table as *const VOID))
+    }
+
+    /// The first table published under `guid`.
+    pub fn find_config_table(&self, guid: &EfiGuid) -> Option<*const VOID> {
+        self.config_tables()
+            .find(|(vendor_guid, _)| vendor_guid == guid)
+            .map(|(_, table)| table)
+    }
+}
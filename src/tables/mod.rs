@@ -2,6 +2,7 @@ pub mod st;
 pub mod bs;
 pub mod rs;
 pub mod hdr;
+pub mod config;
 
 pub use bs::EfiBootServices;
 pub use rs::EfiRuntimeServices;
@@ -1,6 +1,10 @@
+use alloc::vec::Vec;
+
 use super::hdr::*;
 use crate::types::*;
 use crate::safeptr::ThreadSafePtr;
+use crate::variable::*;
+use crate::efi_try;
 
 #[repr(C)]
 pub struct EfiRuntimeServices {
@@ -80,7 +84,8 @@ pub struct EfiRuntimeServices {
         capsule_header_array: *mut *mut EfiCapsuleHeader,
         capsule_count: UINTN,
         maximum_capsule_size: *mut UINT64,
-        reset_type: *mut EfiResetType,
+        // an EFI_RESET_TYPE, read as an integer since firmware may write anything
+        reset_type: *mut UINT32,
     ) -> EfiStatus,
 
     // miscellaneous uefi 2.0 services
@@ -98,5 +103,89 @@ pub struct RuntimeServices {
 }
 
 impl RuntimeServices {
-
+    /// Reads a variable, growing the buffer until the data fits.
+    pub fn get_variable(&self,
+                        name: &str,
+                        vendor_guid: &EfiGuid
+    ) -> Result<(Vec<u8>, VariableAttributes), EfiStatus> {
+        let mut name = variable_name(name);
+        let mut guid = *vendor_guid;
+        let mut data: Vec<u8> = Vec::new();
+        loop {
+            let mut attributes: UINT32 = 0;
+            let mut size = data.len();
+            let status = unsafe {
+                ((*self.services.as_ptr()).get_variable)(
+                    name.as_mut_ptr(),
+                    &mut guid,
+                    &mut attributes,
+                    &mut size,
+                    data.as_mut_ptr() as *mut VOID,
+                )
+            };
+            match status {
+                EfiStatus::BUFFER_TOO_SMALL => data.resize(size, 0),
+                status => {
+                    efi_try!(status)?;
+                    data.truncate(size);
+                    return Ok((data, VariableAttributes(attributes)));
+                }
+            }
+        }
+    }
+
+    /// Creates, replaces or, with `VariableAttributes::APPEND_WRITE`,
+    /// appends to a variable.
+    pub fn set_variable(&self,
+                        name: &str,
+                        vendor_guid: &EfiGuid,
+                        attributes: VariableAttributes,
+                        data: &[u8]
+    ) -> Result<(), EfiStatus> {
+        let mut name = variable_name(name);
+        let mut guid = *vendor_guid;
+        let status = unsafe {
+            ((*self.services.as_ptr()).set_variable)(
+                name.as_mut_ptr(),
+                &mut guid,
+                attributes.bits(),
+                data.len(),
+                data.as_ptr() as *mut VOID,
+            )
+        };
+        efi_try!(status)
+    }
+
+    /// Deletes a variable that isn't protected by authenticated writes.
+    pub fn delete_variable(&self,
+                           name: &str,
+                           vendor_guid: &EfiGuid
+    ) -> Result<(), EfiStatus> {
+        self.set_variable(name, vendor_guid, VariableAttributes::empty(), &[])
+    }
+
+    /// Walks all variables, or only those of `vendor_guid`.
+    pub fn variable_names(&self, vendor_guid: Option<&EfiGuid>) -> VariableNames<'_> {
+        VariableNames::new(self, vendor_guid)
+    }
+
+    /// Storage limits for variables with `attributes`.
+    pub fn query_variable_info(&self,
+                               attributes: VariableAttributes
+    ) -> Result<VariableStorageInfo, EfiStatus> {
+        let mut info = VariableStorageInfo {
+            maximum_variable_storage_size: 0,
+            remaining_variable_storage_size: 0,
+            maximum_variable_size: 0,
+        };
+        let status = unsafe {
+            ((*self.services.as_ptr()).query_variable_info)(
+                attributes.bits(),
+                &mut info.maximum_variable_storage_size,
+                &mut info.remaining_variable_storage_size,
+                &mut info.maximum_variable_size,
+            )
+        };
+        efi_try!(status).map(|_| info)
+    }
 }
@@ -23,9 +23,9 @@ pub const EFI_2_31_SYSTEM_TABLE_REVISION: UINT32 = (2<<16) | (31);
 pub const EFI_2_30_SYSTEM_TABLE_REVISION: UINT32 = (2<<16) | (30);
 pub const EFI_2_20_SYSTEM_TABLE_REVISION: UINT32 = (2<<16) | (20);
 pub const EFI_2_10_SYSTEM_TABLE_REVISION: UINT32 = (2<<16) | (10);
-pub const EFI_2_00_SYSTEM_TABLE_REVISION: UINT32 = (2<<16) | (00);
+pub const EFI_2_00_SYSTEM_TABLE_REVISION: UINT32 = 2<<16;
 pub const EFI_1_10_SYSTEM_TABLE_REVISION: UINT32 = (1<<16) | (10);
-pub const EFI_1_02_SYSTEM_TABLE_REVISION: UINT32 = (1<<16) | (02);
+pub const EFI_1_02_SYSTEM_TABLE_REVISION: UINT32 = (1<<16) | (2);
 
 pub const EFI_SPECIFICATION_VERSION: UINT32 = EFI_SYSTEM_TABLE_REVISION;
 pub const EFI_SYSTEM_TABLE_REVISION: UINT32 = EFI_2_100_SYSTEM_TABLE_REVISION;
@@ -48,9 +48,10 @@ pub struct EfiSystemTable {
 }
 
 #[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct EfiConfigurationTable {
-    vendor_guid: EfiGuid,
-    vendor_table: *mut VOID,
+    pub vendor_guid: EfiGuid,
+    pub vendor_table: *mut VOID,
 }
 
 pub struct SystemTable {
@@ -60,31 +61,57 @@ pub struct SystemTable {
 }
 
 impl SystemTable {
-    pub fn boot_services(&self) -> &EfiBootServices {
-        unsafe { &*(*self.table.as_ptr()).boot_services }
+    /// `None` after `exit_boot_services`.
+    pub fn boot_services(&self) -> Option<&EfiBootServices> {
+        if crate::boot_services_exited() {
+            return None;
+        }
+        unsafe { (*self.table.as_ptr()).boot_services.as_ref() }
     }
 
     pub fn runtime_services(&self) -> &EfiRuntimeServices {
         unsafe { &*(*self.table.as_ptr()).runtime_services }
     }
 
-    pub fn con_in(&self) -> &SimpleTextInputProtocol {
-        self.con_in.call_once(|| {
-        // SAFETY: table pointer and con_in are guaranteed valid by UEFI spec
-        let raw_ptr = unsafe { &mut *(*self.table.as_ptr()).con_in };
-        unsafe { SimpleTextInputProtocol::new(raw_ptr) }
-        });
-        // unwrap() is guaranteed to be safe as we just called call_once()
-        self.con_in.get().unwrap()
+    /// `None` after `exit_boot_services`, or if there is no console.
+    pub fn con_in(&self) -> Option<&SimpleTextInputProtocol> {
+        if crate::boot_services_exited() {
+            return None;
+        }
+        let raw_ptr = unsafe { (*self.table.as_ptr()).con_in };
+        if raw_ptr.is_null() {
+            return None;
+        }
+        // SAFETY: firmware keeps con_in valid until exit_boot_services
+        Some(self.con_in.call_once(|| unsafe { SimpleTextInputProtocol::new(raw_ptr) }))
     }
 
-    pub fn con_out(&self) -> &SimpleTextOutputProtocol {
-        self.con_out.call_once(|| {
-        // SAFETY: table pointer and con_out are guaranteed valid by UEFI spec
-        let raw_ptr = unsafe { &mut *(*self.table.as_ptr()).con_out };
-        unsafe { SimpleTextOutputProtocol::new(raw_ptr) }
-        });
-        // unwrap() is guaranteed to be safe as we just called call_once()
-        self.con_out.get().unwrap()
+    /// `None` after `exit_boot_services`, or if there is no console.
+    pub fn con_out(&self) -> Option<&SimpleTextOutputProtocol> {
+        if crate::boot_services_exited() {
+            return None;
+        }
+        let raw_ptr = unsafe { (*self.table.as_ptr()).con_out };
+        if raw_ptr.is_null() {
+            return None;
+        }
+        // SAFETY: firmware keeps con_out valid until exit_boot_services
+        Some(self.con_out.call_once(|| unsafe { SimpleTextOutputProtocol::new(raw_ptr) }))
+    }
+
+    // The console and boot services fields are only valid before
+    // exit_boot_services. Firmware such as EDK2 clears them itself; the
+    // header CRC is left to the firmware.
+    pub(crate) fn clear_boot_time_fields(&self) {
+        let table = self.table.as_ptr();
+        unsafe {
+            (*table).console_in_handle = core::ptr::null_mut();
+            (*table).con_in = core::ptr::null_mut();
+            (*table).console_out_handle = core::ptr::null_mut();
+            (*table).con_out = core::ptr::null_mut();
+            (*table).standard_error_handle = core::ptr::null_mut();
+            (*table).std_err = core::ptr::null_mut();
+            (*table).boot_services = core::ptr::null_mut();
+        }
     }
 }
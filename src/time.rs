@@ -0,0 +1,276 @@
+//! Wall-clock time from the runtime time services.
+
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::tables::rs::RuntimeServices;
+use crate::types::*;
+use crate::efi_try;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+const MAX_TIME_ZONE: INT16 = 1440;
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+// Days since 1970-01-01 in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month as u8, day as u8)
+}
+
+impl EfiTime {
+    /// A UTC time, or `None` if any field is out of range.
+    pub fn new(year: UINT16,
+               month: UINT8,
+               day: UINT8,
+               hour: UINT8,
+               minute: UINT8,
+               second: UINT8
+    ) -> Option<Self> {
+        let time = EfiTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            pad1: 0,
+            nanosecond: 0,
+            time_zone: 0,
+            daylight: 0,
+            pad2: 0,
+        };
+        time.is_valid().then_some(time)
+    }
+
+    /// Checks every field against the ranges `SetTime` accepts.
+    pub fn validate(&self) -> Result<(), EfiStatus> {
+        let valid = (1900..=9999).contains(&self.year)
+            && (1..=12).contains(&self.month)
+            && self.day >= 1
+            && self.day <= days_in_month(self.year as i64, self.month)
+            && self.hour <= 23
+            && self.minute <= 59
+            && self.second <= 59
+            && self.nanosecond <= 999_999_999
+            && ((-MAX_TIME_ZONE..=MAX_TIME_ZONE).contains(&self.time_zone)
+                || self.time_zone == EFI_UNSPECIFIED_TIMEZONE)
+            && self.daylight & !(EFI_TIME_ADJUST_DAYLIGHT | EFI_TIME_IN_DAYLIGHT) == 0;
+        if valid { Ok(()) } else { Err(EfiStatus::INVALID_PARAMETER) }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    /// The offset from UTC in minutes, or `None` for local time.
+    pub fn time_zone(&self) -> Option<INT16> {
+        (self.time_zone != EFI_UNSPECIFIED_TIMEZONE).then_some(self.time_zone)
+    }
+
+    // Seconds since the epoch, without validation. Local time counts as UTC.
+    fn unix_seconds(&self) -> i64 {
+        let days = days_from_civil(self.year as i64, self.month as i64, self.day as i64);
+        let offset = self.time_zone().unwrap_or(0) as i64 * 60;
+        days * SECONDS_PER_DAY
+            + self.hour as i64 * 3600
+            + self.minute as i64 * 60
+            + self.second as i64
+            - offset
+    }
+
+    /// Seconds since 1970-01-01T00:00:00Z, ignoring `nanosecond`.
+    ///
+    /// `time_zone` is the offset of the local time from UTC; a time with
+    /// `EFI_UNSPECIFIED_TIMEZONE` is taken as UTC. The daylight flags are
+    /// informational and don't shift the result.
+    pub fn to_unix(&self) -> Option<i64> {
+        self.is_valid().then(|| self.unix_seconds())
+    }
+
+    /// The UTC time `seconds` after the epoch, or `None` outside of the
+    /// years 1900-9999.
+    pub fn from_unix(seconds: i64) -> Option<Self> {
+        let days = seconds.div_euclid(SECONDS_PER_DAY);
+        let secs = seconds.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        if !(1900..=9999).contains(&year) {
+            return None;
+        }
+        EfiTime::new(year as UINT16,
+                     month,
+                     day,
+                     (secs / 3600) as UINT8,
+                     (secs % 3600 / 60) as UINT8,
+                     (secs % 60) as UINT8)
+    }
+
+    /// The same instant with a zero offset and no daylight flags.
+    pub fn to_utc(&self) -> Option<Self> {
+        let utc = EfiTime::from_unix(self.to_unix()?)?;
+        Some(EfiTime { nanosecond: self.nanosecond, ..utc })
+    }
+}
+
+/// Orders by the instant described, then by representation so that the
+/// ordering agrees with `Eq`.
+impl Ord for EfiTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.unix_seconds(), self.nanosecond)
+            .cmp(&(other.unix_seconds(), other.nanosecond))
+            .then_with(|| self.to_bytes().cmp(&other.to_bytes()))
+    }
+}
+
+impl PartialOrd for EfiTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// ISO 8601, e.g. `2024-03-14T15:09:26.500000000+01:00`. Local times have
+/// no offset suffix.
+impl fmt::Display for EfiTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+               self.year, self.month, self.day, self.hour, self.minute, self.second)?;
+        if self.nanosecond != 0 {
+            write!(f, ".{:09}", self.nanosecond)?;
+        }
+        match self.time_zone() {
+            None => Ok(()),
+            Some(0) => f.write_str("Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let minutes = offset.unsigned_abs();
+                write!(f, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+impl RuntimeServices {
+    /// Reads the hardware clock. An out-of-range reading is reported as
+    /// `EfiStatus::DEVICE_ERROR`.
+    pub fn get_time(&self) -> Result<(EfiTime, EfiTimeCapabilities), EfiStatus> {
+        let mut time = EfiTime {
+            year: 0,
+            month: 0,
+            day: 0,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            pad1: 0,
+            nanosecond: 0,
+            time_zone: 0,
+            daylight: 0,
+            pad2: 0,
+        };
+        let mut capabilities = EfiTimeCapabilities {
+            resolution: 0,
+            accuracy: 0,
+            sets_to_zero: FALSE,
+        };
+        let status = unsafe {
+            ((*self.services.as_ptr()).get_time)(&mut time, &mut capabilities)
+        };
+        efi_try!(status)?;
+        time.validate().map_err(|_| EfiStatus::DEVICE_ERROR)?;
+        Ok((time, capabilities))
+    }
+
+    /// Sets the hardware clock, rejecting invalid times before calling the
+    /// firmware.
+    pub fn set_time(&self, time: &EfiTime) -> Result<(), EfiStatus> {
+        time.validate()?;
+        let mut time = *time;
+        let status = unsafe { ((*self.services.as_ptr()).set_time)(&mut time) };
+        efi_try!(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn time(year: UINT16, month: UINT8, day: UINT8, hour: UINT8, minute: UINT8) -> EfiTime {
+        EfiTime::new(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn epoch() {
+        let epoch = time(1970, 1, 1, 0, 0);
+        assert_eq!(epoch.to_unix(), Some(0));
+        assert_eq!(EfiTime::from_unix(0), Some(epoch));
+        assert_eq!(epoch.to_string(), "1970-01-01T00:00:00Z");
+        assert_eq!(EfiTime::from_unix(-1), Some(EfiTime { second: 59, ..time(1969, 12, 31, 23, 59) }));
+    }
+
+    #[test]
+    fn leap_day() {
+        let leap = time(2024, 2, 29, 12, 0);
+        assert_eq!(leap.to_unix(), Some(1_709_208_000));
+        assert_eq!(EfiTime::from_unix(1_709_208_000), Some(leap));
+        assert_eq!(EfiTime::from_unix(1_709_208_000 + SECONDS_PER_DAY), Some(time(2024, 3, 1, 12, 0)));
+
+        assert!(EfiTime::new(2000, 2, 29, 0, 0, 0).is_some());
+        assert!(EfiTime::new(2023, 2, 29, 0, 0, 0).is_none());
+        assert!(EfiTime::new(1900, 2, 29, 0, 0, 0).is_none());
+    }
+
+    #[test]
+    fn negative_time_zone_crosses_midnight() {
+        // 21:00 at UTC-05:00 is 02:00 UTC the next day
+        let local = EfiTime { time_zone: -300, ..time(2024, 2, 28, 21, 0) };
+        assert_eq!(local.to_string(), "2024-02-28T21:00:00-05:00");
+        assert_eq!(local.to_utc(), Some(time(2024, 2, 29, 2, 0)));
+        assert_eq!(local.to_unix(), time(2024, 2, 29, 2, 0).to_unix());
+        assert_eq!(local.cmp(&time(2024, 2, 29, 2, 0)), Ordering::Less);
+        assert!(local < time(2024, 2, 29, 2, 1));
+    }
+
+    #[test]
+    fn rejects_out_of_range() {
+        let valid = time(2024, 4, 30, 0, 0);
+        assert!(valid.is_valid());
+        assert_eq!(EfiTime { time_zone: 1441, ..valid }.validate(), Err(EfiStatus::INVALID_PARAMETER));
+        assert_eq!(EfiTime { time_zone: -1441, ..valid }.to_unix(), None);
+        assert!(EfiTime { time_zone: EFI_UNSPECIFIED_TIMEZONE, ..valid }.is_valid());
+        assert!(EfiTime { day: 31, ..valid }.validate().is_err());
+        assert!(EfiTime::new(2024, 4, 31, 0, 0, 0).is_none());
+        assert!(EfiTime::new(2024, 13, 1, 0, 0, 0).is_none());
+        assert_eq!(EfiTime::from_unix(i64::from(i32::MIN) * 2), None);
+    }
+}
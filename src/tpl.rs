@@ -0,0 +1,130 @@
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::types::*;
+use crate::*;
+
+/// Task priority levels usable by applications and drivers.
+#[repr(usize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Tpl {
+    Application = TPL_APPLICATION,
+    Callback = TPL_CALLBACK,
+    Notify = TPL_NOTIFY,
+    HighLevel = TPL_HIGH_LEVEL,
+}
+
+/// Raises the TPL for its lifetime and restores the previous level on drop.
+///
+/// Raising never lowers the TPL: if the current level is already above the
+/// requested one, the current level is kept.
+pub struct TplGuard {
+    old_tpl: Option<EfiTpl>,
+}
+
+impl TplGuard {
+    pub fn raise(tpl: Tpl) -> Self {
+        // no events are dispatched once boot services are gone
+        let Some(bs) = BOOT_SERVICES.get() else {
+            return TplGuard { old_tpl: None };
+        };
+
+        // raising to TPL_HIGH_LEVEL is always allowed and tells us the
+        // current level, from which we can safely drop to the target
+        let old_tpl = unsafe { bs.raise_tpl(TPL_HIGH_LEVEL) };
+        let target = (tpl as EfiTpl).max(old_tpl);
+        if target < TPL_HIGH_LEVEL {
+            unsafe { bs.restore_tpl(target) };
+        }
+        TplGuard { old_tpl: Some(old_tpl) }
+    }
+
+    /// The level that will be restored on drop.
+    pub fn previous(&self) -> Option<EfiTpl> {
+        self.old_tpl
+    }
+}
+
+impl Drop for TplGuard {
+    fn drop(&mut self) {
+        if let (Some(old_tpl), Some(bs)) = (self.old_tpl, BOOT_SERVICES.get()) {
+            unsafe { bs.restore_tpl(old_tpl) };
+        }
+    }
+}
+
+/// Mutual exclusion between the main loop and event notify functions.
+///
+/// Locking raises the TPL to the mutex level, so notify functions at or
+/// below it are held off until the guard is dropped. Locking from a notify
+/// function above that level while the lock is held is a bug and panics
+/// instead of corrupting the data.
+pub struct Mutex<T> {
+    tpl: Tpl,
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Sync for Mutex<T> {}
+unsafe impl<T: Send> Send for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    /// Creates a mutex at `Tpl::Notify`.
+    pub const fn new(data: T) -> Self {
+        Mutex::with_tpl(Tpl::Notify, data)
+    }
+
+    pub const fn with_tpl(tpl: Tpl, data: T) -> Self {
+        Mutex {
+            tpl,
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        self.try_lock().expect("TPL mutex re-entered from a higher TPL")
+    }
+
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        let tpl = TplGuard::raise(self.tpl);
+        if self.locked.swap(true, Ordering::Acquire) {
+            return None;
+        }
+        Some(MutexGuard { mutex: self, _tpl: tpl })
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+pub struct MutexGuard<'a, T> {
+    mutex: &'a Mutex<T>,
+    // dropped after the lock flag is cleared
+    _tpl: TplGuard,
+}
+
+impl<T> core::ops::Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T> core::ops::DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.locked.store(false, Ordering::Release);
+    }
+}
@@ -18,7 +18,7 @@ pub const FALSE: BOOLEAN = 0;
 pub const TRUE: BOOLEAN = 1;
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct EfiGuid {
     pub data1: u32,
     pub data2: u16,
@@ -26,11 +26,100 @@ pub struct EfiGuid {
     pub data4: [u8; 8],
 }
 
+impl EfiGuid {
+    /// Reads a GUID in its mixed-endian wire format.
+    pub fn from_bytes(bytes: [u8; 16]) -> Self {
+        EfiGuid {
+            data1: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            data2: u16::from_le_bytes([bytes[4], bytes[5]]),
+            data3: u16::from_le_bytes([bytes[6], bytes[7]]),
+            data4: [bytes[8], bytes[9], bytes[10], bytes[11],
+                    bytes[12], bytes[13], bytes[14], bytes[15]],
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0u8; 16];
+        bytes[0..4].copy_from_slice(&self.data1.to_le_bytes());
+        bytes[4..6].copy_from_slice(&self.data2.to_le_bytes());
+        bytes[6..8].copy_from_slice(&self.data3.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.data4);
+        bytes
+    }
+}
+
+impl core::fmt::Display for EfiGuid {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-",
+               self.data1, self.data2, self.data3,
+               self.data4[0], self.data4[1])?;
+        for byte in &self.data4[2..] {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
 pub type EfiHandle = PVOID;
 pub type EfiEvent = PVOID;
 pub type EfiLba = UINT64;
 pub type EfiTpl = UINTN;
 
+pub const TPL_APPLICATION: EfiTpl   = 4;
+pub const TPL_CALLBACK: EfiTpl      = 8;
+pub const TPL_NOTIFY: EfiTpl        = 16;
+pub const TPL_HIGH_LEVEL: EfiTpl    = 31;
+
+pub type EfiEventNotify = unsafe extern "efiapi" fn(
+    event: EfiEvent,
+    context: *mut VOID,
+);
+
+pub const EVT_TIMER: UINT32                         = 0x80000000;
+pub const EVT_RUNTIME: UINT32                       = 0x40000000;
+pub const EVT_NOTIFY_WAIT: UINT32                   = 0x00000100;
+pub const EVT_NOTIFY_SIGNAL: UINT32                 = 0x00000200;
+pub const EVT_SIGNAL_EXIT_BOOT_SERVICES: UINT32     = 0x00000201;
+pub const EVT_SIGNAL_VIRTUAL_ADDRESS_CHANGE: UINT32 = 0x60000202;
+
+#[repr(i32)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EfiTimerDelay {
+    TimerCancel,
+    TimerPeriodic,
+    TimerRelative,
+}
+
+#[repr(i32)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EfiInterfaceType {
+    EfiNativeInterface,
+}
+
+#[repr(i32)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EfiLocateSearchType {
+    AllHandles,
+    ByRegisterNotify,
+    ByProtocol,
+}
+
+pub const EFI_OPEN_PROTOCOL_BY_HANDLE_PROTOCOL: UINT32  = 0x00000001;
+pub const EFI_OPEN_PROTOCOL_GET_PROTOCOL: UINT32        = 0x00000002;
+pub const EFI_OPEN_PROTOCOL_TEST_PROTOCOL: UINT32       = 0x00000004;
+pub const EFI_OPEN_PROTOCOL_BY_CHILD_CONTROLLER: UINT32 = 0x00000008;
+pub const EFI_OPEN_PROTOCOL_BY_DRIVER: UINT32           = 0x00000010;
+pub const EFI_OPEN_PROTOCOL_EXCLUSIVE: UINT32           = 0x00000020;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct EfiOpenProtocolInformationEntry {
+    pub agent_handle: EfiHandle,
+    pub controller_handle: EfiHandle,
+    pub attributes: UINT32,
+    pub open_count: UINT32,
+}
+
 #[repr(C)]
 pub struct EfiMacAddress {
     pub addr: [u8; 32],
@@ -59,6 +148,7 @@ pub struct EfiInputKey {
 }
 
 #[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct EfiTime {
     pub year: UINT16,
     pub month: UINT8,
@@ -73,12 +163,50 @@ pub struct EfiTime {
     pub pad2: UINT8,
 }
 
+impl EfiTime {
+    pub const SIZE: usize = 16;
+
+    /// Reads an `EFI_TIME` in its little-endian wire format.
+    pub fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        EfiTime {
+            year: u16::from_le_bytes([bytes[0], bytes[1]]),
+            month: bytes[2],
+            day: bytes[3],
+            hour: bytes[4],
+            minute: bytes[5],
+            second: bytes[6],
+            pad1: bytes[7],
+            nanosecond: u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+            time_zone: i16::from_le_bytes([bytes[12], bytes[13]]),
+            daylight: bytes[14],
+            pad2: bytes[15],
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[0..2].copy_from_slice(&self.year.to_le_bytes());
+        bytes[2] = self.month;
+        bytes[3] = self.day;
+        bytes[4] = self.hour;
+        bytes[5] = self.minute;
+        bytes[6] = self.second;
+        bytes[7] = self.pad1;
+        bytes[8..12].copy_from_slice(&self.nanosecond.to_le_bytes());
+        bytes[12..14].copy_from_slice(&self.time_zone.to_le_bytes());
+        bytes[14] = self.daylight;
+        bytes[15] = self.pad2;
+        bytes
+    }
+}
+
 pub const EFI_TIME_ADJUST_DAYLIGHT: UINT8 = 0x01;
 pub const EFI_TIME_IN_DAYLIGHT: UINT8 = 0x02;
 
 pub const EFI_UNSPECIFIED_TIMEZONE: INT16 = 0x07FF;
 
 #[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct EfiTimeCapabilities {
     pub resolution: UINT32,
     pub accuracy: UINT32,
@@ -88,7 +216,11 @@ pub struct EfiTimeCapabilities {
 pub type EfiPhysicalAddress = UINT64;
 pub type EfiVirtualAddress = UINT64;
 
+pub const EFI_PAGE_SIZE: UINTN = 4096;
+pub const EFI_PAGE_SHIFT: UINTN = 12;
+
 #[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct EfiMemoryDescriptor {
     pub mem_type: UINT32,
     pub physical_start: EfiPhysicalAddress,
@@ -97,7 +229,26 @@ pub struct EfiMemoryDescriptor {
     pub attribute: UINT64,
 }
 
+pub const EFI_MEMORY_UC: UINT64             = 0x0000000000000001;
+pub const EFI_MEMORY_WC: UINT64             = 0x0000000000000002;
+pub const EFI_MEMORY_WT: UINT64             = 0x0000000000000004;
+pub const EFI_MEMORY_WB: UINT64             = 0x0000000000000008;
+pub const EFI_MEMORY_UCE: UINT64            = 0x0000000000000010;
+pub const EFI_MEMORY_WP: UINT64             = 0x0000000000001000;
+pub const EFI_MEMORY_RP: UINT64             = 0x0000000000002000;
+pub const EFI_MEMORY_XP: UINT64             = 0x0000000000004000;
+pub const EFI_MEMORY_NV: UINT64             = 0x0000000000008000;
+pub const EFI_MEMORY_MORE_RELIABLE: UINT64  = 0x0000000000010000;
+pub const EFI_MEMORY_RO: UINT64             = 0x0000000000020000;
+pub const EFI_MEMORY_SP: UINT64             = 0x0000000000040000;
+pub const EFI_MEMORY_CPU_CRYPTO: UINT64     = 0x0000000000080000;
+pub const EFI_MEMORY_RUNTIME: UINT64        = 0x8000000000000000;
+
+/// `convert_pointer` disposition allowing a null pointer.
+pub const EFI_OPTIONAL_PTR: UINTN = 0x00000001;
+
 #[repr(i32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum EfiResetType {
     EfiResetCold,
     EfiResetWarm,
@@ -105,7 +256,24 @@ pub enum EfiResetType {
     EfiResetPlatformSpecific,
 }
 
+/// Checks a reset type written by firmware; an unknown value is handed back
+/// as the error.
+impl TryFrom<UINT32> for EfiResetType {
+    type Error = UINT32;
+
+    fn try_from(raw: UINT32) -> Result<Self, UINT32> {
+        match raw {
+            0 => Ok(EfiResetType::EfiResetCold),
+            1 => Ok(EfiResetType::EfiResetWarm),
+            2 => Ok(EfiResetType::EfiResetShutdown),
+            3 => Ok(EfiResetType::EfiResetPlatformSpecific),
+            _ => Err(raw),
+        }
+    }
+}
+
 #[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub struct EfiCapsuleHeader {
     pub capsule_guid: EfiGuid,
     pub header_size: UINT32,
@@ -117,7 +285,18 @@ pub const CAPSULE_FLAGS_PERSIST_ACROSS_RESET: UINT32    = 0x00010000;
 pub const CAPSULE_FLAGS_POPULATE_SYSTEM_TABLE: UINT32   = 0x00020000;
 pub const CAPSULE_FLAGS_INITIATE_RESET: UINT32          = 0x00040000;
 
+/// One entry of an update_capsule scatter-gather list. A zero `length`
+/// makes `address` a continuation pointer to the next list, or ends the
+/// list when `address` is zero too.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EfiCapsuleBlockDescriptor {
+    pub length: UINT64,
+    pub address: EfiPhysicalAddress,
+}
+
 #[repr(i32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum EfiMemoryType {
     EfiReservedMemoryType,
     EfiLoaderCode,
@@ -139,6 +318,7 @@ pub enum EfiMemoryType {
 }
 
 #[repr(i32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum EfiAllocateType {
     AllocateAnyPages,
     AllocateMaxAddress,
@@ -146,30 +326,178 @@ pub enum EfiAllocateType {
     MaxAllocateType
 }
 
-#[repr(usize)]
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub enum EfiStatus {
-    EfiSuccess = 0,
-    EfiLoadError = 1,
-    EfiInvalidParameter = 2,
-    EfiUnsupported = 3,
-    EfiBadBufferSize = 4,
-    EfiBufferTooSmall = 5,
-    EfiAlreadyStarted = 20,
+pub const EFI_ERROR_BIT: UINTN = 1 << (UINTN::BITS - 1);
+
+#[repr(transparent)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EfiStatus(pub UINTN);
+
+macro_rules! efi_status_codes {
+    ($($name:ident = $value:expr, $text:literal;)*) => {
+        impl EfiStatus {
+            $(pub const $name: EfiStatus = EfiStatus($value);)*
+
+            /// Spec name of the status code, if it is a known one.
+            pub fn name(&self) -> Option<&'static str> {
+                match *self {
+                    $(EfiStatus::$name => Some(concat!("EFI_", stringify!($name))),)*
+                    _ => None,
+                }
+            }
+
+            /// Spec description of the status code, if it is a known one.
+            pub fn description(&self) -> Option<&'static str> {
+                match *self {
+                    $(EfiStatus::$name => Some($text),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+efi_status_codes! {
+    SUCCESS = 0,
+        "The operation completed successfully.";
+
+    WARN_UNKNOWN_GLYPH = 1,
+        "The string contained one or more characters that the device could not render and were skipped.";
+    WARN_DELETE_FAILURE = 2,
+        "The handle was closed, but the file was not deleted.";
+    WARN_WRITE_FAILURE = 3,
+        "The handle was closed, but the data to the file was not flushed properly.";
+    WARN_BUFFER_TOO_SMALL = 4,
+        "The resulting buffer was too small, and the data was truncated to the buffer size.";
+    WARN_STALE_DATA = 5,
+        "The data has not been updated within the timeframe set by local policy for this type of data.";
+    WARN_FILE_SYSTEM = 6,
+        "The resulting buffer contains UEFI-compliant file system.";
+    WARN_RESET_REQUIRED = 7,
+        "The operation will be processed across a system reset.";
+
+    LOAD_ERROR = EFI_ERROR_BIT | 1,
+        "The image failed to load.";
+    INVALID_PARAMETER = EFI_ERROR_BIT | 2,
+        "A parameter was incorrect.";
+    UNSUPPORTED = EFI_ERROR_BIT | 3,
+        "The operation is not supported.";
+    BAD_BUFFER_SIZE = EFI_ERROR_BIT | 4,
+        "The buffer was not the proper size for the request.";
+    BUFFER_TOO_SMALL = EFI_ERROR_BIT | 5,
+        "The buffer is not large enough to hold the requested data.";
+    NOT_READY = EFI_ERROR_BIT | 6,
+        "There is no data pending upon return.";
+    DEVICE_ERROR = EFI_ERROR_BIT | 7,
+        "The physical device reported an error while attempting the operation.";
+    WRITE_PROTECTED = EFI_ERROR_BIT | 8,
+        "The device cannot be written to.";
+    OUT_OF_RESOURCES = EFI_ERROR_BIT | 9,
+        "A resource has run out.";
+    VOLUME_CORRUPTED = EFI_ERROR_BIT | 10,
+        "An inconstancy was detected on the file system causing the operation to fail.";
+    VOLUME_FULL = EFI_ERROR_BIT | 11,
+        "There is no more space on the file system.";
+    NO_MEDIA = EFI_ERROR_BIT | 12,
+        "The device does not contain any medium to perform the operation.";
+    MEDIA_CHANGED = EFI_ERROR_BIT | 13,
+        "The medium in the device has changed since the last access.";
+    NOT_FOUND = EFI_ERROR_BIT | 14,
+        "The item was not found.";
+    ACCESS_DENIED = EFI_ERROR_BIT | 15,
+        "Access was denied.";
+    NO_RESPONSE = EFI_ERROR_BIT | 16,
+        "The server was not found or did not respond to the request.";
+    NO_MAPPING = EFI_ERROR_BIT | 17,
+        "A mapping to a device does not exist.";
+    TIMEOUT = EFI_ERROR_BIT | 18,
+        "The timeout time expired.";
+    NOT_STARTED = EFI_ERROR_BIT | 19,
+        "The protocol has not been started.";
+    ALREADY_STARTED = EFI_ERROR_BIT | 20,
+        "The protocol has already been started.";
+    ABORTED = EFI_ERROR_BIT | 21,
+        "The operation was aborted.";
+    ICMP_ERROR = EFI_ERROR_BIT | 22,
+        "An ICMP error occurred during the network operation.";
+    TFTP_ERROR = EFI_ERROR_BIT | 23,
+        "A TFTP error occurred during the network operation.";
+    PROTOCOL_ERROR = EFI_ERROR_BIT | 24,
+        "A protocol error occurred during the network operation.";
+    INCOMPATIBLE_VERSION = EFI_ERROR_BIT | 25,
+        "The function encountered an internal version that was incompatible with a version requested by the caller.";
+    SECURITY_VIOLATION = EFI_ERROR_BIT | 26,
+        "The function was not performed due to a security violation.";
+    CRC_ERROR = EFI_ERROR_BIT | 27,
+        "A CRC error was detected.";
+    END_OF_MEDIA = EFI_ERROR_BIT | 28,
+        "Beginning or end of media was reached.";
+    END_OF_FILE = EFI_ERROR_BIT | 31,
+        "The end of the file was reached.";
+    INVALID_LANGUAGE = EFI_ERROR_BIT | 32,
+        "The language specified was invalid.";
+    COMPROMISED_DATA = EFI_ERROR_BIT | 33,
+        "The security status of the data is unknown or compromised and the data must be updated or replaced to restore a valid security status.";
+    IP_ADDRESS_CONFLICT = EFI_ERROR_BIT | 34,
+        "There is an address conflict address allocation.";
+    HTTP_ERROR = EFI_ERROR_BIT | 35,
+        "A HTTP error occurred during the network operation.";
 }
 
 impl EfiStatus {
     pub fn is_success(&self) -> bool {
-        *self == EfiStatus::EfiSuccess
+        *self == EfiStatus::SUCCESS
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.0 & EFI_ERROR_BIT != 0
+    }
+
+    pub fn is_warning(&self) -> bool {
+        !self.is_success() && !self.is_error()
+    }
+}
+
+impl From<UINTN> for EfiStatus {
+    fn from(raw: UINTN) -> Self {
+        EfiStatus(raw)
+    }
+}
+
+impl From<EfiStatus> for UINTN {
+    fn from(status: EfiStatus) -> Self {
+        status.0
+    }
+}
+
+impl core::fmt::Debug for EfiStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "EfiStatus({})", name),
+            None => write!(f, "EfiStatus({:#x})", self.0),
+        }
     }
 }
 
+impl core::fmt::Display for EfiStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match (self.name(), self.description()) {
+            (Some(name), Some(text)) => write!(f, "{}: {}", name, text),
+            _ if self.is_error() => {
+                write!(f, "unknown error {:#x}", self.0 & !EFI_ERROR_BIT)
+            }
+            _ => write!(f, "unknown warning {:#x}", self.0),
+        }
+    }
+}
+
+impl core::error::Error for EfiStatus {}
+
 #[repr(C)]
 pub struct SimpleTextOutputMode {
-    max_mode: INT32,
-    mode: INT32,
-    attribute: INT32,
-    cursor_column: INT32,
-    cursor_row: INT32,
-    cursor_visible: BOOLEAN,
+    pub max_mode: INT32,
+    pub mode: INT32,
+    pub attribute: INT32,
+    pub cursor_column: INT32,
+    pub cursor_row: INT32,
+    pub cursor_visible: BOOLEAN,
 }
@@ -0,0 +1,166 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::tables::rs::RuntimeServices;
+use crate::types::*;
+
+/// Namespace of the architecturally defined variables, e.g. `BootOrder`.
+pub const EFI_GLOBAL_VARIABLE: EfiGuid = EfiGuid {
+    data1: 0x8be4df61,
+    data2: 0x93ca,
+    data3: 0x11d2,
+    data4: [0xaa,0x0d,0x00,0xe0,0x98,0x03,0x2b,0x8c],
+};
+
+/// Attributes of a UEFI variable, combined with `|`.
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct VariableAttributes(pub UINT32);
+
+impl VariableAttributes {
+    pub const NON_VOLATILE: Self                            = Self(0x00000001);
+    pub const BOOTSERVICE_ACCESS: Self                      = Self(0x00000002);
+    pub const RUNTIME_ACCESS: Self                          = Self(0x00000004);
+    pub const HARDWARE_ERROR_RECORD: Self                   = Self(0x00000008);
+    pub const AUTHENTICATED_WRITE_ACCESS: Self              = Self(0x00000010);
+    pub const TIME_BASED_AUTHENTICATED_WRITE_ACCESS: Self   = Self(0x00000020);
+    pub const APPEND_WRITE: Self                            = Self(0x00000040);
+    pub const ENHANCED_AUTHENTICATED_ACCESS: Self           = Self(0x00000080);
+
+    /// `NON_VOLATILE | BOOTSERVICE_ACCESS | RUNTIME_ACCESS`, the usual set
+    /// for persistent variables.
+    pub const NV_BS_RT: Self = Self(0x00000007);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn bits(&self) -> UINT32 {
+        self.0
+    }
+
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl core::ops::BitOr for VariableAttributes {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl core::ops::BitOrAssign for VariableAttributes {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl core::ops::BitAnd for VariableAttributes {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl core::ops::Not for VariableAttributes {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+/// Storage limits reported by `query_variable_info`, in bytes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VariableStorageInfo {
+    pub maximum_variable_storage_size: UINT64,
+    pub remaining_variable_storage_size: UINT64,
+    pub maximum_variable_size: UINT64,
+}
+
+/// Null-terminated UTF-16 copy of a variable name.
+pub(crate) fn variable_name(name: &str) -> Vec<CHAR16> {
+    name.encode_utf16().chain(core::iter::once(0)).collect()
+}
+
+/// Iterator over variable names and vendor GUIDs, from
+/// [`RuntimeServices::variable_names`].
+///
+/// Stops after the last variable or after yielding the first error.
+/// Creating or deleting variables while iterating may restart or skip
+/// entries, as the firmware walks its store in place.
+pub struct VariableNames<'a> {
+    services: &'a RuntimeServices,
+    name: Vec<CHAR16>,
+    vendor_guid: EfiGuid,
+    filter: Option<EfiGuid>,
+    done: bool,
+}
+
+impl<'a> VariableNames<'a> {
+    pub(crate) fn new(services: &'a RuntimeServices, filter: Option<&EfiGuid>) -> Self {
+        VariableNames {
+            services,
+            // an empty name starts the walk
+            name: alloc::vec![0; 64],
+            vendor_guid: EfiGuid { data1: 0, data2: 0, data3: 0, data4: [0; 8] },
+            filter: filter.copied(),
+            done: false,
+        }
+    }
+
+    fn advance(&mut self) -> Result<bool, EfiStatus> {
+        loop {
+            let mut size = core::mem::size_of_val(self.name.as_slice());
+            let status = unsafe {
+                ((*self.services.services.as_ptr()).get_next_variable_name)(
+                    &mut size,
+                    self.name.as_mut_ptr(),
+                    &mut self.vendor_guid,
+                )
+            };
+            match status {
+                EfiStatus::SUCCESS => return Ok(true),
+                EfiStatus::NOT_FOUND => return Ok(false),
+                // the previous name stays in place, only the buffer grows
+                EfiStatus::BUFFER_TOO_SMALL => {
+                    self.name.resize(size.div_ceil(core::mem::size_of::<CHAR16>()), 0)
+                }
+                status => return Err(status),
+            }
+        }
+    }
+}
+
+impl Iterator for VariableNames<'_> {
+    type Item = Result<(String, EfiGuid), EfiStatus>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.advance() {
+                Ok(true) => {
+                    if self.filter.is_some_and(|guid| guid != self.vendor_guid) {
+                        continue;
+                    }
+                    let len = self.name.iter().position(|&c| c == 0).unwrap_or(self.name.len());
+                    let name = String::from_utf16_lossy(&self.name[..len]);
+                    return Some(Ok((name, self.vendor_guid)));
+                }
+                Ok(false) => self.done = true,
+                Err(status) => {
+                    self.done = true;
+                    return Some(Err(status));
+                }
+            }
+        }
+        None
+    }
+}